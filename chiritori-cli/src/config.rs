@@ -0,0 +1,656 @@
+use chiritori::chiritori::{BeforeRelease, RemovalStrategy};
+use chiritori::code::utils::datetime_parser::parse_time_offset;
+use chiritori::preset::{self, LANGUAGE_PRESETS};
+use serde::de::value::{MapAccessDeserializer, SeqAccessDeserializer};
+use serde::de::{MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const CONFIG_FILENAME: &str = "chiritori.toml";
+const AVAILABLE_STRATEGIES: &str =
+    "\"range\", \"unwrap-block\", \"unwrap-else\", \"unwrap-indent\", \"strip-tags\"";
+
+/// The contents of `chiritori.toml`.
+///
+/// ```toml
+/// [delimiters]
+/// start = "<!-- <"
+/// end = "> -->"
+///
+/// [delimiters.extensions.js]
+/// start = "/* <"
+/// end = "> */"
+///
+/// # Multiple delimiter pairs for mixed-language files
+/// [[delimiters.extensions.vue]]
+/// start = "<!-- <"
+/// end = "> -->"
+/// [[delimiters.extensions.vue]]
+/// start = "/* <"
+/// end = "> */"
+///
+/// # Override the built-in language preset
+/// [languages.python]
+/// delimiters = [{ start = "# <", end = "> #" }]
+///
+/// # Add a language
+/// [languages.terraform]
+/// extensions = ["tf"]
+/// delimiters = [{ start = "/* <", end = "> */" }, { start = "# <", end = "\n" }]
+///
+/// [time-limited]
+/// tag-name = "time-limited"
+/// time-offset = "+09:00"
+///
+/// [time-released]
+/// tag-name = "time-released"
+/// before-release = "remove"
+///
+/// [removal-marker]
+/// tag-name = "removal-marker"
+/// targets = ["feature1"]
+///
+/// [strategies]
+/// enabled = ["range", "unwrap-block", "unwrap-else", "unwrap-indent", "strip-tags"]
+///
+/// # The strategy used for all elements of the tag
+/// [strategies.tags]
+/// removal-marker = "strip-tags"
+/// ```
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ConfigFile {
+    #[serde(default)]
+    pub delimiters: DelimitersConfig,
+    #[serde(default)]
+    pub languages: HashMap<String, LanguageConfig>,
+    #[serde(default)]
+    pub time_limited: TimeLimitedConfig,
+    #[serde(default)]
+    pub time_released: TimeReleasedConfig,
+    #[serde(default)]
+    pub removal_marker: RemovalMarkerConfig,
+    #[serde(default)]
+    pub strategies: StrategiesConfig,
+}
+
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct DelimitersConfig {
+    pub start: Option<String>,
+    pub end: Option<String>,
+    #[serde(default)]
+    pub extensions: HashMap<String, DelimiterPairs>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct DelimiterPair {
+    pub start: String,
+    pub end: String,
+}
+
+/// A delimiter pair (a table) or delimiter pairs (an array of tables) for an extension.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DelimiterPairs(pub Vec<DelimiterPair>);
+
+impl DelimiterPairs {
+    pub fn to_pairs(&self) -> Vec<(String, String)> {
+        self.0
+            .iter()
+            .map(|pair| (pair.start.clone(), pair.end.clone()))
+            .collect()
+    }
+}
+
+impl<'de> Deserialize<'de> for DelimiterPairs {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct DelimiterPairsVisitor;
+
+        impl<'de> Visitor<'de> for DelimiterPairsVisitor {
+            type Value = DelimiterPairs;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a delimiter pair or an array of delimiter pairs")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                let pair = DelimiterPair::deserialize(MapAccessDeserializer::new(map))?;
+                Ok(DelimiterPairs(vec![pair]))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+                let pairs = Vec::<DelimiterPair>::deserialize(SeqAccessDeserializer::new(seq))?;
+                Ok(DelimiterPairs(pairs))
+            }
+        }
+
+        deserializer.deserialize_any(DelimiterPairsVisitor)
+    }
+}
+
+/// A language added to the built-in presets, or overriding the built-in preset with the same name.
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct LanguageConfig {
+    pub extensions: Option<Vec<String>>,
+    pub delimiters: Option<DelimiterPairs>,
+}
+
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct TimeLimitedConfig {
+    pub tag_name: Option<String>,
+    pub time_offset: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct TimeReleasedConfig {
+    pub tag_name: Option<String>,
+    pub before_release: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct RemovalMarkerConfig {
+    pub tag_name: Option<String>,
+    pub targets: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct StrategiesConfig {
+    pub enabled: Option<Vec<String>>,
+    #[serde(default)]
+    pub tags: HashMap<String, String>,
+}
+
+impl ConfigFile {
+    /// Delimiter pairs for the file.
+    /// Delimiters for the extension take precedence over the language in the config file inferred from the extension,
+    /// the language takes precedence over the default ones (`[delimiters] start / end`),
+    /// and the default ones written in the config file take precedence over the built-in presets.
+    pub fn delimiters_for(&self, path: Option<&Path>) -> Vec<(Option<String>, Option<String>)> {
+        let extension = path
+            .and_then(|p| p.extension())
+            .map(|ext| ext.to_string_lossy().to_string());
+        let language = extension
+            .as_deref()
+            .and_then(|ext| self.language_for_extension(ext));
+        let is_default_configured =
+            self.delimiters.start.is_some() || self.delimiters.end.is_some();
+        let by_extension = extension
+            .as_deref()
+            .and_then(|ext| self.delimiters.extensions.get(ext))
+            .map(|pairs| pairs.to_pairs())
+            .or_else(|| {
+                language
+                    .and_then(|name| self.languages.get(name)?.delimiters.as_ref())
+                    .map(|pairs| pairs.to_pairs())
+            })
+            .or_else(|| {
+                language
+                    .filter(|_| !is_default_configured)
+                    .and_then(preset::find)
+                    .map(|p| p.delimiter_pairs())
+            });
+
+        match by_extension {
+            Some(pairs) => pairs
+                .into_iter()
+                .map(|(start, end)| (Some(start), Some(end)))
+                .collect(),
+            None => vec![(self.delimiters.start.clone(), self.delimiters.end.clone())],
+        }
+    }
+
+    /// The language of the file inferred from the extension.
+    pub fn language_for(&self, path: Option<&Path>) -> Option<String> {
+        let extension = path?.extension()?.to_string_lossy();
+
+        self.language_for_extension(&extension)
+            .map(|name| name.to_string())
+    }
+
+    /// Delimiter pairs of the language.
+    /// The language in the config file takes precedence over the built-in preset.
+    pub fn language_delimiters(&self, name: &str) -> Option<Vec<(String, String)>> {
+        match self.languages.get(name).and_then(|l| l.delimiters.as_ref()) {
+            Some(pairs) => Some(pairs.to_pairs()),
+            None => preset::find(name).map(|p| p.delimiter_pairs()),
+        }
+    }
+
+    /// Names of the available languages, sorted.
+    pub fn language_names(&self) -> Vec<&str> {
+        let mut names: Vec<_> = LANGUAGE_PRESETS
+            .iter()
+            .map(|p| p.name)
+            .chain(self.languages.keys().map(|name| name.as_str()))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        names.sort();
+
+        names
+    }
+
+    fn language_for_extension(&self, extension: &str) -> Option<&str> {
+        let mut names: Vec<_> = self
+            .languages
+            .iter()
+            .filter(|(_, l)| {
+                l.extensions
+                    .as_ref()
+                    .is_some_and(|exts| exts.iter().any(|e| e == extension))
+            })
+            .map(|(name, _)| name.as_str())
+            .collect();
+        names.sort();
+
+        names.first().copied().or_else(|| {
+            // Extensions of the built-in preset are replaced when they are overridden.
+            preset::find_by_extension(extension)
+                .filter(|p| {
+                    self.languages
+                        .get(p.name)
+                        .is_none_or(|l| l.extensions.is_none())
+                })
+                .map(|p| p.name)
+        })
+    }
+
+    pub fn enabled_strategies(&self) -> Option<HashSet<RemovalStrategy>> {
+        self.strategies
+            .enabled
+            .as_ref()
+            .map(|names| names.iter().filter_map(|name| name.parse().ok()).collect())
+    }
+
+    pub fn time_released_before_release(&self) -> Option<BeforeRelease> {
+        self.time_released
+            .before_release
+            .as_ref()
+            .and_then(|value| value.parse().ok())
+    }
+
+    pub fn tag_strategies(&self) -> HashMap<String, RemovalStrategy> {
+        self.strategies
+            .tags
+            .iter()
+            .filter_map(|(tag_name, name)| Some((tag_name.clone(), name.parse().ok()?)))
+            .collect()
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let mut delimiters = vec![
+            (
+                "delimiters.start".to_string(),
+                self.delimiters.start.as_deref(),
+            ),
+            ("delimiters.end".to_string(), self.delimiters.end.as_deref()),
+        ];
+        for (ext, pairs) in &self.delimiters.extensions {
+            if pairs.0.is_empty() {
+                return Err(format!("`delimiters.extensions.{ext}` must not be empty"));
+            }
+        }
+        for (name, language) in &self.languages {
+            match &language.delimiters {
+                None if preset::find(name).is_none() => {
+                    return Err(format!("`languages.{name}.delimiters` is required"));
+                }
+                Some(pairs) if pairs.0.is_empty() => {
+                    return Err(format!("`languages.{name}.delimiters` must not be empty"));
+                }
+                Some(pairs) => {
+                    for pair in &pairs.0 {
+                        delimiters.push((
+                            format!("languages.{name}.delimiters.start"),
+                            Some(pair.start.as_str()),
+                        ));
+                        delimiters.push((
+                            format!("languages.{name}.delimiters.end"),
+                            Some(pair.end.as_str()),
+                        ));
+                    }
+                }
+                None => {}
+            }
+        }
+        for (ext, pair) in self
+            .delimiters
+            .extensions
+            .iter()
+            .flat_map(|(ext, pairs)| pairs.0.iter().map(move |pair| (ext, pair)))
+        {
+            delimiters.push((
+                format!("delimiters.extensions.{ext}.start"),
+                Some(pair.start.as_str()),
+            ));
+            delimiters.push((
+                format!("delimiters.extensions.{ext}.end"),
+                Some(pair.end.as_str()),
+            ));
+        }
+        if let Some((key, _)) = delimiters
+            .iter()
+            .find(|(_, value)| value.is_some_and(|v| v.is_empty()))
+        {
+            return Err(format!("`{key}` must not be empty"));
+        }
+
+        for (key, value) in [
+            ("time-limited.tag-name", &self.time_limited.tag_name),
+            ("time-released.tag-name", &self.time_released.tag_name),
+            ("removal-marker.tag-name", &self.removal_marker.tag_name),
+        ] {
+            if value.as_ref().is_some_and(|v| v.trim().is_empty()) {
+                return Err(format!("`{key}` must not be empty"));
+            }
+        }
+
+        if let Some(time_offset) = &self.time_limited.time_offset {
+            if parse_time_offset(time_offset).is_err() {
+                return Err(format!(
+                    "`time-limited.time-offset` is invalid: \"{time_offset}\" (expected a format like \"+09:00\")"
+                ));
+            }
+        }
+
+        if let Some(before_release) = &self.time_released.before_release {
+            if let Err(e) = before_release.parse::<BeforeRelease>() {
+                return Err(format!("`time-released.before-release` is invalid: {e}"));
+            }
+        }
+
+        if let Some(enabled) = &self.strategies.enabled {
+            if let Some(e) = enabled
+                .iter()
+                .find_map(|name| name.parse::<RemovalStrategy>().err())
+            {
+                return Err(format!(
+                    "`strategies.enabled` is invalid: {e} (available: {AVAILABLE_STRATEGIES})"
+                ));
+            }
+        }
+        for (tag_name, name) in &self.strategies.tags {
+            if let Err(e) = name.parse::<RemovalStrategy>() {
+                return Err(format!(
+                    "`strategies.tags.{tag_name}` is invalid: {e} (available: {AVAILABLE_STRATEGIES})"
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Find `chiritori.toml` in the directory or its ancestors.
+pub fn discover(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|d| d.join(CONFIG_FILENAME))
+        .find(|p| p.is_file())
+}
+
+pub fn load(path: &Path) -> Result<ConfigFile, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    parse(&content).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+pub fn parse(content: &str) -> Result<ConfigFile, String> {
+    let config: ConfigFile = toml::from_str(content).map_err(|e| e.to_string())?;
+    config.validate()?;
+
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[test]
+    fn test_parse() {
+        let config = parse(
+            r#"
+[delimiters]
+start = "/* <"
+end = "> */"
+
+[delimiters.extensions.html]
+start = "<!-- <"
+end = "> -->"
+
+[[delimiters.extensions.vue]]
+start = "<!-- <"
+end = "> -->"
+
+[[delimiters.extensions.vue]]
+start = "/* <"
+end = "> */"
+
+[time-limited]
+tag-name = "tl"
+time-offset = "+09:00"
+
+[time-released]
+before-release = "remove"
+
+[removal-marker]
+targets = ["feature1", "feature2"]
+
+[strategies]
+enabled = ["range"]
+
+[strategies.tags]
+removal-marker = "strip-tags"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.delimiters_for(Some(Path::new("foo/bar.html"))),
+            vec![(Some("<!-- <".to_string()), Some("> -->".to_string()))]
+        );
+        assert_eq!(
+            config.delimiters_for(Some(Path::new("foo/bar.vue"))),
+            vec![
+                (Some("<!-- <".to_string()), Some("> -->".to_string())),
+                (Some("/* <".to_string()), Some("> */".to_string()))
+            ]
+        );
+        // The default delimiters in the config file take precedence over the built-in preset.
+        assert_eq!(
+            config.delimiters_for(Some(Path::new("foo/bar.py"))),
+            vec![(Some("/* <".to_string()), Some("> */".to_string()))]
+        );
+        assert_eq!(
+            config.delimiters_for(Some(Path::new("foo/bar.txt"))),
+            vec![(Some("/* <".to_string()), Some("> */".to_string()))]
+        );
+        assert_eq!(
+            config.delimiters_for(None),
+            vec![(Some("/* <".to_string()), Some("> */".to_string()))]
+        );
+        assert_eq!(config.time_limited.tag_name, Some("tl".to_string()));
+        assert_eq!(config.time_limited.time_offset, Some("+09:00".to_string()));
+        assert_eq!(config.time_released.tag_name, None);
+        assert_eq!(
+            config.time_released_before_release(),
+            Some(BeforeRelease::Remove)
+        );
+        assert_eq!(config.removal_marker.tag_name, None);
+        assert_eq!(
+            config.removal_marker.targets,
+            Some(vec!["feature1".to_string(), "feature2".to_string()])
+        );
+        assert_eq!(
+            config.enabled_strategies(),
+            Some(HashSet::from([RemovalStrategy::Range]))
+        );
+        assert_eq!(
+            config.tag_strategies(),
+            HashMap::from([("removal-marker".to_string(), RemovalStrategy::StripTags)])
+        );
+    }
+
+    #[test]
+    fn test_languages() {
+        let config = parse(
+            r##"
+[delimiters.extensions.rb]
+start = "=begin <"
+end = "> =end"
+
+[languages.python]
+delimiters = [{ start = "# <", end = "> #" }]
+
+[languages.shell]
+extensions = ["command"]
+
+[languages.terraform]
+extensions = ["tf"]
+
+[[languages.terraform.delimiters]]
+start = "/* <"
+end = "> */"
+
+[[languages.terraform.delimiters]]
+start = "# <"
+end = "\n"
+"##,
+        )
+        .unwrap();
+
+        let delimiters_for = |path: &str| {
+            config
+                .delimiters_for(Some(Path::new(path)))
+                .into_iter()
+                .map(|(start, end)| (start.unwrap(), end.unwrap()))
+                .collect::<Vec<_>>()
+        };
+        let pair = |start: &str, end: &str| (start.to_string(), end.to_string());
+
+        // Overridden delimiters of the built-in preset
+        assert_eq!(delimiters_for("main.py"), vec![pair("# <", "> #")]);
+        // Added language
+        assert_eq!(
+            delimiters_for("main.tf"),
+            vec![pair("/* <", "> */"), pair("# <", "\n")]
+        );
+        // Delimiters for the extension take precedence over the built-in preset
+        assert_eq!(delimiters_for("main.rb"), vec![pair("=begin <", "> =end")]);
+        // Overridden extensions of the built-in preset
+        assert_eq!(delimiters_for("main.command"), vec![pair("# <", "\n")]);
+        assert_eq!(
+            config.delimiters_for(Some(Path::new("main.sh"))),
+            vec![(None, None)]
+        );
+        // The built-in preset
+        assert_eq!(
+            delimiters_for("main.go"),
+            vec![pair("/* <", "> */"), pair("// <", "\n")]
+        );
+
+        assert_eq!(
+            config.language_delimiters("terraform"),
+            Some(vec![pair("/* <", "> */"), pair("# <", "\n")])
+        );
+        assert_eq!(
+            config.language_delimiters("html"),
+            Some(vec![pair("<!-- <", "> -->")])
+        );
+        assert_eq!(config.language_delimiters("unknown"), None);
+        assert!(config.language_names().contains(&"terraform"));
+
+        assert_eq!(
+            config.language_for(Some(Path::new("main.tf"))),
+            Some("terraform".to_string())
+        );
+        assert_eq!(
+            config.language_for(Some(Path::new("main.go"))),
+            Some("go".to_string())
+        );
+        assert_eq!(config.language_for(Some(Path::new("main.unknown"))), None);
+        assert_eq!(config.language_for(None), None);
+    }
+
+    #[test]
+    fn test_parse_empty() {
+        assert_eq!(parse("").unwrap(), ConfigFile::default());
+    }
+
+    #[rstest]
+    #[case("[delimiters]\nstart = 1", "invalid type")]
+    #[case("[delimiters]\nstart = \"\"", "`delimiters.start` must not be empty")]
+    #[case(
+        "[delimiters.extensions.js]\nstart = \"\"\nend = \"a\"",
+        "`delimiters.extensions.js.start` must not be empty"
+    )]
+    #[case("[delimiters.extensions.js]\nstart = \"a\"", "missing field `end`")]
+    #[case(
+        "[[delimiters.extensions.vue]]\nstart = \"a\"\nend = \"b\"\n[[delimiters.extensions.vue]]\nstart = \"\"\nend = \"b\"",
+        "`delimiters.extensions.vue.start` must not be empty"
+    )]
+    #[case(
+        "[delimiters]\nextensions = { vue = [] }",
+        "`delimiters.extensions.vue` must not be empty"
+    )]
+    #[case(
+        "[delimiters]\nextensions = { vue = \"a\" }",
+        "a delimiter pair or an array of delimiter pairs"
+    )]
+    #[case(
+        "[languages.foo]\nextensions = [\"foo\"]",
+        "`languages.foo.delimiters` is required"
+    )]
+    #[case(
+        "[languages.foo]\ndelimiters = []",
+        "`languages.foo.delimiters` must not be empty"
+    )]
+    #[case(
+        "[languages.python]\ndelimiters = { start = \"\", end = \"a\" }",
+        "`languages.python.delimiters.start` must not be empty"
+    )]
+    #[case(
+        "[time-limited]\ntag-name = \" \"",
+        "`time-limited.tag-name` must not be empty"
+    )]
+    #[case(
+        "[time-limited]\ntime-offset = \"9:00\"",
+        "`time-limited.time-offset` is invalid"
+    )]
+    #[case(
+        "[time-released]\nbefore-release = \"hide\"",
+        "`time-released.before-release` is invalid: Unknown action before release: hide"
+    )]
+    #[case("[strategies]\nenabled = [\"foo\"]", "Unknown removal strategy: foo")]
+    #[case(
+        "[strategies.tags]\nremoval-marker = \"strip\"",
+        "`strategies.tags.removal-marker` is invalid: Unknown removal strategy: strip"
+    )]
+    #[case("[time-limited]\nfoo = 1", "unknown field `foo`")]
+    fn test_parse_invalid(#[case] content: &str, #[case] expected_message: &str) {
+        let error = parse(content).unwrap_err();
+        assert!(
+            error.contains(expected_message),
+            "\"{error}\" does not contain \"{expected_message}\""
+        );
+    }
+
+    #[test]
+    fn test_discover() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("a/b");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(dir.path().join(CONFIG_FILENAME), "").unwrap();
+
+        assert_eq!(discover(&nested), Some(dir.path().join(CONFIG_FILENAME)));
+    }
+}
@@ -0,0 +1,252 @@
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+use std::str::FromStr;
+
+/// The number of leading bytes inspected when detecting UTF-16 without a byte order mark.
+const UTF16_DETECTION_LEN: usize = 8000;
+
+/// The encoding given by `--encoding`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum EncodingOption {
+    /// Detect the encoding from the content
+    Auto,
+    Fixed(&'static Encoding),
+}
+
+impl FromStr for EncodingOption {
+    type Err = String;
+
+    /// Parse `auto` or a label of the encoding (e.g. `shift_jis`, `euc-jp`, `utf-16le`, `latin1`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("auto") {
+            return Ok(EncodingOption::Auto);
+        }
+
+        Encoding::for_label(s.as_bytes())
+            .map(EncodingOption::Fixed)
+            .ok_or_else(|| format!("Unknown encoding: {}", s))
+    }
+}
+
+/// The encoding of a source file. The output is written back in the same encoding
+/// with the byte order mark if the source has it.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TextEncoding {
+    encoding: &'static Encoding,
+    bom: bool,
+}
+
+impl TextEncoding {
+    /// Detect the encoding of the bytes.
+    /// A byte order mark takes precedence over the option.
+    pub fn detect(bytes: &[u8], option: EncodingOption) -> Self {
+        if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+            return Self {
+                encoding,
+                bom: true,
+            };
+        }
+
+        let encoding = match option {
+            EncodingOption::Fixed(encoding) => encoding,
+            EncodingOption::Auto => detect_without_bom(bytes),
+        };
+
+        Self {
+            encoding,
+            bom: false,
+        }
+    }
+
+    pub fn without_bom(self) -> Self {
+        Self { bom: false, ..self }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.encoding.name()
+    }
+
+    /// UTF-16 text contains NUL bytes, so it must not be taken as a binary file.
+    pub fn is_utf16(&self) -> bool {
+        self.encoding == UTF_16LE || self.encoding == UTF_16BE
+    }
+
+    /// Decode the bytes without the byte order mark. `None` if the bytes are malformed.
+    pub fn decode(&self, bytes: &[u8]) -> Option<String> {
+        let bytes = match self.bom {
+            true => &bytes[bom(self.encoding).len()..],
+            false => bytes,
+        };
+
+        self.encoding
+            .decode_without_bom_handling_and_without_replacement(bytes)
+            .map(|content| content.into_owned())
+    }
+
+    /// Encode the content, adding the byte order mark if the source has it.
+    /// `None` if the content has characters that cannot be encoded.
+    pub fn encode(&self, content: &str) -> Option<Vec<u8>> {
+        let mut bytes = match self.bom {
+            true => bom(self.encoding).to_vec(),
+            false => vec![],
+        };
+
+        // encoding_rs does not encode into UTF-16 (it is replaced with UTF-8 as defined by the WHATWG).
+        if self.encoding == UTF_16LE {
+            bytes.extend(content.encode_utf16().flat_map(u16::to_le_bytes));
+        } else if self.encoding == UTF_16BE {
+            bytes.extend(content.encode_utf16().flat_map(u16::to_be_bytes));
+        } else {
+            let (encoded, _, had_errors) = self.encoding.encode(content);
+            if had_errors {
+                return None;
+            }
+            bytes.extend_from_slice(&encoded);
+        }
+
+        Some(bytes)
+    }
+}
+
+impl Default for TextEncoding {
+    fn default() -> Self {
+        Self {
+            encoding: UTF_8,
+            bom: false,
+        }
+    }
+}
+
+fn bom(encoding: &'static Encoding) -> &'static [u8] {
+    if encoding == UTF_16LE {
+        b"\xFF\xFE"
+    } else if encoding == UTF_16BE {
+        b"\xFE\xFF"
+    } else {
+        b"\xEF\xBB\xBF"
+    }
+}
+
+fn detect_without_bom(bytes: &[u8]) -> &'static Encoding {
+    if let Some(encoding) = detect_utf16(bytes) {
+        return encoding;
+    }
+    if std::str::from_utf8(bytes).is_ok() {
+        return UTF_8;
+    }
+
+    let mut detector = EncodingDetector::new();
+    detector.feed(bytes, true);
+    detector.guess(None, true)
+}
+
+/// Detect UTF-16 without a byte order mark from the NUL bytes of ASCII characters.
+///
+/// ```text
+///  UTF-16LE: 61 00 62 00  (zeros at odd positions)
+///  UTF-16BE: 00 61 00 62  (zeros at even positions)
+/// ```
+fn detect_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    let units: Vec<_> = bytes[..bytes.len().min(UTF16_DETECTION_LEN)]
+        .chunks_exact(2)
+        .collect();
+    if units.len() < 2 {
+        return None;
+    }
+
+    let even_zeros = units.iter().filter(|u| u[0] == 0).count();
+    let odd_zeros = units.iter().filter(|u| u[1] == 0).count();
+
+    if odd_zeros * 2 > units.len() && even_zeros == 0 {
+        Some(UTF_16LE)
+    } else if even_zeros * 2 > units.len() && odd_zeros == 0 {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_rs::{EUC_JP, SHIFT_JIS, WINDOWS_1252};
+    use rstest::rstest;
+
+    const JAPANESE_TEXT: &str = "<!-- <time-limited to=\"2024-01-01\"> -->\nキャンペーン実施中です。お早めにお申し込みください。\n<!-- </time-limited> -->\n";
+
+    #[rstest]
+    #[case("auto", Ok(EncodingOption::Auto))]
+    #[case("Shift_JIS", Ok(EncodingOption::Fixed(SHIFT_JIS)))]
+    #[case("sjis", Ok(EncodingOption::Fixed(SHIFT_JIS)))]
+    #[case("euc-jp", Ok(EncodingOption::Fixed(EUC_JP)))]
+    #[case("latin1", Ok(EncodingOption::Fixed(WINDOWS_1252)))]
+    #[case("utf-16", Ok(EncodingOption::Fixed(UTF_16LE)))]
+    #[case("foo", Err("Unknown encoding: foo".to_string()))]
+    fn test_parse_encoding_option(
+        #[case] value: &str,
+        #[case] expected: Result<EncodingOption, String>,
+    ) {
+        assert_eq!(value.parse(), expected);
+    }
+
+    #[rstest]
+    #[case(SHIFT_JIS)]
+    #[case(EUC_JP)]
+    #[case(UTF_8)]
+    fn test_detect(#[case] encoding: &'static Encoding) {
+        let (bytes, _, _) = encoding.encode(JAPANESE_TEXT);
+
+        let detected = TextEncoding::detect(&bytes, EncodingOption::Auto);
+        assert_eq!(detected.name(), encoding.name());
+        assert_eq!(detected.decode(&bytes).as_deref(), Some(JAPANESE_TEXT));
+    }
+
+    #[test]
+    fn test_detect_latin1() {
+        let bytes = b"caf\xE9 cr\xE8me br\xFBl\xE9e, d\xE9j\xE0 vu\n";
+
+        let detected = TextEncoding::detect(bytes, EncodingOption::Auto);
+        assert_eq!(detected.name(), "windows-1252");
+        assert_eq!(
+            detected.decode(bytes).as_deref(),
+            Some("café crème brûlée, déjà vu\n")
+        );
+    }
+
+    #[rstest]
+    #[case(b"\xFF\xFEa\x00b\x00", "UTF-16LE")]
+    #[case(b"\xFE\xFF\x00a\x00b", "UTF-16BE")]
+    #[case(b"\xEF\xBB\xBFab", "UTF-8")]
+    #[case(b"a\x00b\x00", "UTF-16LE")]
+    #[case(b"\x00a\x00b", "UTF-16BE")]
+    fn test_round_trip(#[case] bytes: &[u8], #[case] name: &str) {
+        let detected = TextEncoding::detect(bytes, EncodingOption::Auto);
+        assert_eq!(detected.name(), name);
+
+        let content = detected.decode(bytes).unwrap();
+        assert_eq!(content, "ab");
+        assert_eq!(detected.encode(&content).as_deref(), Some(bytes));
+    }
+
+    #[test]
+    fn test_detect_with_bom() {
+        // The byte order mark takes precedence over the option.
+        let detected = TextEncoding::detect(b"\xFF\xFEa\x00", EncodingOption::Fixed(SHIFT_JIS));
+
+        assert_eq!(detected.name(), "UTF-16LE");
+    }
+
+    #[test]
+    fn test_decode_malformed() {
+        let detected = TextEncoding::detect(b"a\xFFb", EncodingOption::Fixed(UTF_8));
+
+        assert_eq!(detected.decode(b"a\xFFb"), None);
+    }
+
+    #[test]
+    fn test_encode_unmappable() {
+        let encoding = TextEncoding::detect(b"", EncodingOption::Fixed(SHIFT_JIS));
+
+        assert_eq!(encoding.encode("a😀"), None);
+    }
+}
@@ -0,0 +1,102 @@
+use chiritori::chiritori::ChiritoriError;
+use chiritori::code::utils::datetime_parser::DateTimeParseError;
+use std::io;
+use thiserror::Error;
+
+/// Exit code when the command line arguments are invalid (the same as clap)
+const EXIT_CODE_USAGE: i32 = 2;
+/// Exit code when the config file (or the configuration given by arguments) is invalid
+const EXIT_CODE_CONFIG: i32 = 3;
+/// Exit code when a file cannot be read or written
+const EXIT_CODE_IO: i32 = 4;
+/// Exit code when the input cannot be decoded or the output cannot be encoded
+const EXIT_CODE_ENCODING: i32 = 5;
+/// Exit code when elements cannot be evaluated in the strict mode
+const EXIT_CODE_INVALID_ELEMENTS: i32 = 6;
+/// Exit code when the output cannot be serialized
+const EXIT_CODE_SERIALIZATION: i32 = 7;
+
+#[derive(Error, Debug)]
+pub enum CliError {
+    #[error("{0}")]
+    Usage(String),
+    #[error("Invalid config file: {0}")]
+    Config(String),
+    /// The current time given by `--now` or `SOURCE_DATE_EPOCH` is invalid
+    #[error("{name}: {source}")]
+    CurrentTime {
+        name: &'static str,
+        #[source]
+        source: DateTimeParseError,
+    },
+    #[error("{path}: {source}")]
+    Io {
+        path: String,
+        #[source]
+        source: io::Error,
+    },
+    #[error("{path}: The input is not {encoding} text")]
+    Encoding {
+        path: String,
+        encoding: &'static str,
+    },
+    #[error("{path}: The output cannot be encoded in {encoding}")]
+    Unencodable {
+        path: String,
+        encoding: &'static str,
+    },
+    #[error("Some elements cannot be evaluated (--strict)")]
+    InvalidElements,
+    #[error("Failed to serialize JSON: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error(transparent)]
+    Chiritori(#[from] ChiritoriError),
+}
+
+impl CliError {
+    /// Build an error from an I/O error. Invalid UTF-8 input is reported as an encoding error.
+    pub fn io(path: impl Into<String>, source: io::Error) -> Self {
+        let path = path.into();
+
+        match source.kind() {
+            io::ErrorKind::InvalidData => CliError::Encoding {
+                path,
+                encoding: "UTF-8",
+            },
+            _ => CliError::Io { path, source },
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Usage(_) => EXIT_CODE_USAGE,
+            CliError::Config(_) | CliError::CurrentTime { .. } => EXIT_CODE_CONFIG,
+            CliError::Io { .. } => EXIT_CODE_IO,
+            CliError::Encoding { .. } | CliError::Unencodable { .. } => EXIT_CODE_ENCODING,
+            CliError::InvalidElements => EXIT_CODE_INVALID_ELEMENTS,
+            CliError::Serialization(_) => EXIT_CODE_SERIALIZATION,
+            CliError::Chiritori(ChiritoriError::List(_)) => EXIT_CODE_SERIALIZATION,
+            CliError::Chiritori(_) => EXIT_CODE_CONFIG,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(io::ErrorKind::NotFound, EXIT_CODE_IO, "a.js: No such file")]
+    #[case(
+        io::ErrorKind::InvalidData,
+        EXIT_CODE_ENCODING,
+        "a.js: The input is not UTF-8 text"
+    )]
+    fn test_io(#[case] kind: io::ErrorKind, #[case] exit_code: i32, #[case] message: &str) {
+        let error = CliError::io("a.js", io::Error::new(kind, "No such file"));
+
+        assert_eq!(error.exit_code(), exit_code);
+        assert_eq!(error.to_string(), message);
+    }
+}
@@ -1,56 +1,143 @@
 extern crate chiritori;
 use chiritori::chiritori::{
-    clean, list, list_all, ChiritoriConfiguration, ListFormat, RemovalMarkerConfiguration,
-    TimeLimitedConfiguration,
+    build, builder::Chiritori, BeforeRelease, ChiritoriConfiguration, ChiritoriError, ListFormat,
+    RemovalMarkerConfiguration, RemovalStrategyConfiguration, TimeLimitedConfiguration,
+    TimeReleasedConfiguration,
 };
-use clap::Parser;
-use std::collections::HashSet;
+use chiritori::code::diff::build_unified_diff;
+use chiritori::code::remover::InvalidElement;
+use chiritori::code::utils::datetime_parser::{parse_current_datetime, parse_unix_timestamp};
+use chiritori::code::utils::line_map::build_line_map;
+use chiritori::diagnostic::{Diagnostic, Severity};
+use chiritori::tokenizer::END_OF_LINE;
+use chrono::{DateTime, Local};
+use clap::{Parser, Subcommand, ValueEnum};
+use encoding::{EncodingOption, TextEncoding};
+use error::CliError;
+use std::collections::{hash_map::Entry, HashMap, HashSet};
 use std::fs::File;
 use std::io::{prelude::*, BufReader};
-use std::rc::Rc;
+use std::path::{Path, PathBuf};
+
+mod config;
+mod encoding;
+mod error;
+mod source;
+mod writer;
+
+const DEFAULT_DELIMITER_START: &str = "<!-- <";
+const DEFAULT_DELIMITER_END: &str = "> -->";
+const DEFAULT_TIME_LIMITED_TAG_NAME: &str = "time-limited";
+const DEFAULT_TIME_LIMITED_TIME_OFFSET: &str = "+00:00";
+const DEFAULT_TIME_RELEASED_TAG_NAME: &str = "time-released";
+const DEFAULT_REMOVAL_MARKER_TAG_NAME: &str = "removal-marker";
+
+/// Exit code of `--check` when there are elements ready to be removed
+const EXIT_CODE_REMOVALS_PENDING: i32 = 1;
+/// Exit code of `lint` when there are problems
+const EXIT_CODE_LINT_PROBLEMS: i32 = 1;
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Files or directories to process. Directories are walked recursively.
+    #[arg(conflicts_with = "filename")]
+    paths: Vec<String>,
+
     /// The filename to read
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     filename: Option<String>,
 
+    /// Glob pattern of files to process in directories (can be specified multiple times)
+    #[arg(long, global = true)]
+    include: Vec<String>,
+
+    /// Glob pattern of files to skip in directories (can be specified multiple times)
+    #[arg(long, global = true)]
+    exclude: Vec<String>,
+
+    /// Process files ignored by .gitignore, .ignore and hidden files in directories
+    #[arg(long, global = true)]
+    no_ignore: bool,
+
     /// The filename to write
     #[arg(short, long)]
     output: Option<String>,
 
-    /// The delimiter start
-    #[arg(long, default_value = "<!-- <")]
-    delimiter_start: String,
+    /// Rewrite the processed files in place. Unchanged files are not touched.
+    #[arg(short, long, conflicts_with_all = ["output", "list", "list_all"])]
+    in_place: bool,
+
+    /// Keep the original files with the `.orig` suffix when rewriting them in place
+    #[arg(long, requires = "in_place")]
+    backup: bool,
+
+    /// The config file (default: chiritori.toml in the current directory or its ancestors)
+    #[arg(long, global = true)]
+    config: Option<String>,
+
+    /// Do not load the config file
+    #[arg(long, conflicts_with = "config", global = true)]
+    no_config: bool,
+
+    /// The delimiter start [default: "<!-- <"].
+    /// Repeat it with `--delimiter-end` to use multiple delimiter pairs in one pass.
+    #[arg(long, global = true)]
+    delimiter_start: Vec<String>,
+
+    /// The delimiter end [default: "> -->"].
+    /// Repeat it with `--delimiter-start` to use multiple delimiter pairs in one pass.
+    #[arg(long, global = true)]
+    delimiter_end: Vec<String>,
 
-    /// The delimiter end
-    #[arg(long, default_value = "> -->")]
-    delimiter_end: String,
+    /// Close tags at the end of the line instead of the delimiter end.
+    /// It is for languages that have only line comments (e.g. `--delimiter-start="# <" --delimiter-end-eol`).
+    /// The whole comment line is removed with the tag.
+    #[arg(long, global = true, conflicts_with = "delimiter_end")]
+    delimiter_end_eol: bool,
 
-    /// The tag name for time-limited content
-    #[arg(long, default_value = "time-limited")]
-    time_limited_tag_name: String,
+    /// Use the delimiters of the language preset (e.g. html, js, python).
+    /// Without this option, the language is inferred from the file extension.
+    #[arg(long, global = true, conflicts_with_all = ["delimiter_start", "delimiter_end", "delimiter_end_eol"])]
+    lang: Option<String>,
 
-    /// The time offset for time-limited content
-    #[arg(long, default_value = "+00:00")]
-    time_limited_time_offset: String,
+    /// The tag name for time-limited content [default: time-limited]
+    #[arg(long, global = true)]
+    time_limited_tag_name: Option<String>,
 
-    /// The current time for time-limited content
-    #[arg(long, default_value = "")]
-    time_limited_current: String,
+    /// The time offset for time-limited content [default: +00:00]
+    #[arg(long, global = true)]
+    time_limited_time_offset: Option<String>,
 
-    /// The tag name for removal-marker
-    #[arg(long, default_value = "removal-marker")]
-    removal_marker_tag_name: String,
+    /// The current time for time-limited content, to preview the source code at another time.
+    /// A date time, a month (e.g. 2025-03) or a relative expression (e.g. +7d, tomorrow, next monday).
+    /// [default: SOURCE_DATE_EPOCH if it is set, otherwise the current time]
+    #[arg(long, visible_alias = "now", global = true)]
+    time_limited_current: Option<String>,
+
+    /// The tag name for time-released content [default: time-released]
+    #[arg(long, global = true)]
+    time_released_tag_name: Option<String>,
+
+    /// What to do with time-released content before the release date:
+    /// "keep" leaves the block as it is, and "remove" removes the block with the content [default: keep]
+    #[arg(long, global = true)]
+    time_released_before_release: Option<BeforeRelease>,
+
+    /// The tag name for removal-marker [default: removal-marker]
+    #[arg(long, global = true)]
+    removal_marker_tag_name: Option<String>,
 
     /// Name of removal-marker to be removed
-    #[arg(long, default_value = "vec![]")]
+    #[arg(long, global = true)]
     removal_marker_target_name: Vec<String>,
 
     /// Config file specifying the name of the removal-marker to be removed.
     /// The content of the config file is indicated by the name of the removal target, separated by a newline.
-    #[arg(long)]
+    #[arg(long, global = true)]
     removal_marker_target_config: Option<String>,
 
     /// List source code to be removed
@@ -64,90 +151,800 @@ struct Args {
     /// Output the list in JSON format
     #[arg(long)]
     list_json: bool,
+
+    /// List source code ready to be removed without modifying anything, and exit with 1 if there is any.
+    /// The exit code is 0 if nothing is ready to be removed, and 2 or more on errors.
+    #[arg(long, conflicts_with_all = ["output", "in_place", "list", "list_all"])]
+    check: bool,
+
+    /// Output a unified diff of the changes instead of the processed source code.
+    /// The diff can be applied with `git apply`.
+    #[arg(long, conflicts_with_all = ["in_place", "list", "list_all", "check"])]
+    diff: bool,
+
+    /// Fail with exit code 6 when an element cannot be evaluated (e.g. a malformed date of time-limited).
+    /// Without this option, such elements are reported as warnings and left as they are.
+    #[arg(long)]
+    strict: bool,
+
+    /// The character encoding of the source code (e.g. shift_jis, euc-jp, utf-16le, latin1),
+    /// or "auto" to detect it from the content. The processed source code is written in the same encoding.
+    /// A byte order mark takes precedence over this option and is kept in the output.
+    #[arg(long, default_value = "utf-8", global = true)]
+    encoding: EncodingOption,
+
+    /// The format of errors and warnings found in the source code
+    #[arg(long, value_enum, default_value_t = MessageFormat::Human, global = true)]
+    message_format: MessageFormat,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum MessageFormat {
+    /// With the source line and caret underlines
+    Human,
+    /// One line per message
+    Short,
+    /// One JSON object per line
+    Json,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Validate the structure of tags
+    ///
+    /// Unclosed tags, orphan closing tags, crossed nesting, typos in tag names and attributes
+    /// that fail to parse are reported with their line and column.
+    /// The exit code is 1 if there is any problem, and 2 or more on errors.
+    Lint {
+        /// Files or directories to lint. Directories are walked recursively.
+        #[arg(conflicts_with = "filename")]
+        paths: Vec<String>,
+    },
 }
 
 fn main() {
     let args = Args::parse();
 
-    let mut content = String::new();
-    if args.filename.is_none() {
-        if atty::isnt(atty::Stream::Stdin) {
-            std::io::stdin()
-                .read_to_string(&mut content)
-                .expect("something went wrong reading the file");
-        } else {
-            println!("No input file or stdin. More information: --help");
-            std::process::exit(1);
+    match run(args) {
+        Ok(exit_code) => std::process::exit(exit_code),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(e.exit_code());
+        }
+    }
+}
+
+/// Run the command and return the exit code.
+fn run(args: Args) -> Result<i32, CliError> {
+    validate_delimiter_args(&args).map_err(CliError::Usage)?;
+
+    let config_file = load_config_file(&args)?;
+
+    if let Some(lang) = &args.lang {
+        if config_file.language_delimiters(lang).is_none() {
+            return Err(CliError::Usage(format!(
+                "Unknown language: {} (available: {})",
+                lang,
+                config_file.language_names().join(", ")
+            )));
         }
+    }
+    let config = build_configuration(&args, &config_file)?;
+
+    let mut chiritori = ChiritoriCache::new(&args, &config_file, config);
+
+    if args.in_place {
+        rewrite_in_place(&args, &mut chiritori)?;
+        return Ok(0);
+    }
+
+    if let Some(Command::Lint { paths }) = &args.command {
+        return run_lint(&args, paths, &mut chiritori);
+    }
+
+    if args.check {
+        return run_check(&args, &mut chiritori);
+    }
+
+    let output = if args.paths.is_empty() {
+        let (content, encoding) = read_single_input(&args)?;
+        let path = args.filename.as_ref().map(Path::new);
+        let output = process(&args, chiritori.get(path)?, &content, path)?;
+
+        encode_output(&args, output, encoding, path)?
+    } else {
+        process_paths(&args, &mut chiritori)?
+    };
+
+    if let Some(filename) = &args.output {
+        std::fs::write(filename, output).map_err(|e| CliError::io(filename, e))?;
+    } else {
+        std::io::stdout()
+            .write_all(&output)
+            .map_err(|e| CliError::io("stdout", e))?;
+    }
+
+    Ok(0)
+}
+
+/// Read the source code given by `--filename` or stdin, and decode it.
+fn read_single_input(args: &Args) -> Result<(String, TextEncoding), CliError> {
+    let mut bytes = vec![];
+    let path = if let Some(filename) = &args.filename {
+        File::open(filename)
+            .and_then(|mut f| f.read_to_end(&mut bytes))
+            .map_err(|e| CliError::io(filename, e))?;
+        filename.as_str()
+    } else if atty::isnt(atty::Stream::Stdin) {
+        std::io::stdin()
+            .read_to_end(&mut bytes)
+            .map_err(|e| CliError::io("stdin", e))?;
+        "stdin"
     } else {
-        let mut f = File::open(args.filename.unwrap()).expect("file not found");
-        f.read_to_string(&mut content)
-            .expect("something went wrong reading the file");
+        return Err(CliError::Usage(
+            "No input file or stdin. More information: --help".to_string(),
+        ));
+    };
+
+    let encoding = TextEncoding::detect(&bytes, args.encoding);
+    let content = encoding.decode(&bytes).ok_or_else(|| CliError::Encoding {
+        path: path.to_string(),
+        encoding: encoding.name(),
+    })?;
+
+    Ok((content, encoding))
+}
+
+fn load_config_file(args: &Args) -> Result<config::ConfigFile, CliError> {
+    if args.no_config {
+        return Ok(config::ConfigFile::default());
     }
 
+    let path = match &args.config {
+        Some(path) => Some(PathBuf::from(path)),
+        None => std::env::current_dir()
+            .ok()
+            .and_then(|dir| config::discover(&dir)),
+    };
+
+    match path {
+        Some(path) => config::load(&path).map_err(CliError::Config),
+        None => Ok(config::ConfigFile::default()),
+    }
+}
+
+/// Resolve the current time to evaluate time-limited tags.
+///
+/// `SOURCE_DATE_EPOCH` replaces the clock for reproducible builds, and relative expressions of `--now`
+/// are based on it. Invalid values are errors rather than falling back to the clock,
+/// since the output would silently differ from the intended date.
+fn resolve_current_time(
+    now: Option<&str>,
+    source_date_epoch: Option<&str>,
+    time_offset: &str,
+) -> Result<DateTime<Local>, CliError> {
+    let base = match source_date_epoch.filter(|value| !value.is_empty()) {
+        Some(value) => parse_unix_timestamp(value).map_err(|source| CliError::CurrentTime {
+            name: "SOURCE_DATE_EPOCH",
+            source,
+        })?,
+        None => Local::now().fixed_offset(),
+    };
+
+    let current = match now {
+        Some(value) => parse_current_datetime(value, base, time_offset).map_err(|source| {
+            CliError::CurrentTime {
+                name: "--now",
+                source,
+            }
+        })?,
+        None => base,
+    };
+
+    Ok(current.with_timezone(&Local))
+}
+
+/// Build the configuration. Command line arguments take precedence over the config file.
+fn build_configuration(
+    args: &Args,
+    config_file: &config::ConfigFile,
+) -> Result<ChiritoriConfiguration, CliError> {
     let removal_marker_target_names_from_file =
-        if let Some(removal_marker_target_config) = args.removal_marker_target_config {
-            load_removal_marker_target_names(removal_marker_target_config)
+        if let Some(removal_marker_target_config) = &args.removal_marker_target_config {
+            load_removal_marker_target_names(removal_marker_target_config)?
         } else {
             vec![]
         };
 
-    let marker_removal_tags: HashSet<_> = removal_marker_target_names_from_file
-        .into_iter()
-        .chain(args.removal_marker_target_name)
-        .collect();
+    let marker_removal_tags: HashSet<_> = if removal_marker_target_names_from_file.is_empty()
+        && args.removal_marker_target_name.is_empty()
+    {
+        config_file
+            .removal_marker
+            .targets
+            .clone()
+            .unwrap_or_default()
+            .into_iter()
+            .collect()
+    } else {
+        removal_marker_target_names_from_file
+            .into_iter()
+            .chain(args.removal_marker_target_name.clone())
+            .collect()
+    };
+
+    let time_offset = args
+        .time_limited_time_offset
+        .clone()
+        .or(config_file.time_limited.time_offset.clone())
+        .unwrap_or(DEFAULT_TIME_LIMITED_TIME_OFFSET.to_string());
+    let current = resolve_current_time(
+        args.time_limited_current.as_deref(),
+        std::env::var("SOURCE_DATE_EPOCH").ok().as_deref(),
+        &time_offset,
+    )?;
 
-    let config = ChiritoriConfiguration {
+    Ok(ChiritoriConfiguration {
         time_limited_configuration: TimeLimitedConfiguration {
-            tag_name: args.time_limited_tag_name,
-            time_offset: args.time_limited_time_offset,
-            current: args
-                .time_limited_current
-                .parse::<chrono::DateTime<chrono::Local>>()
-                .unwrap_or(chrono::Local::now()),
+            tag_name: args
+                .time_limited_tag_name
+                .clone()
+                .or(config_file.time_limited.tag_name.clone())
+                .unwrap_or(DEFAULT_TIME_LIMITED_TAG_NAME.to_string()),
+            time_offset,
+            current,
+        },
+        time_released_configuration: TimeReleasedConfiguration {
+            tag_name: args
+                .time_released_tag_name
+                .clone()
+                .or(config_file.time_released.tag_name.clone())
+                .unwrap_or(DEFAULT_TIME_RELEASED_TAG_NAME.to_string()),
+            before_release: args
+                .time_released_before_release
+                .or(config_file.time_released_before_release())
+                .unwrap_or_default(),
         },
         removal_marker_configuration: RemovalMarkerConfiguration {
-            tag_name: args.removal_marker_tag_name,
+            tag_name: args
+                .removal_marker_tag_name
+                .clone()
+                .or(config_file.removal_marker.tag_name.clone())
+                .unwrap_or(DEFAULT_REMOVAL_MARKER_TAG_NAME.to_string()),
             targets: marker_removal_tags,
         },
+        removal_strategy_configuration: RemovalStrategyConfiguration {
+            enabled_strategies: config_file
+                .enabled_strategies()
+                .unwrap_or(RemovalStrategyConfiguration::default().enabled_strategies),
+            tag_strategies: config_file.tag_strategies(),
+            // The language is resolved for each file.
+            language: None,
+        },
+    })
+}
+
+/// The configuration for the file with the language given by `--lang` or inferred from the file extension.
+fn configuration_for(
+    args: &Args,
+    config_file: &config::ConfigFile,
+    config: &ChiritoriConfiguration,
+    path: Option<&Path>,
+) -> ChiritoriConfiguration {
+    let mut config = config.clone();
+    config.removal_strategy_configuration.language =
+        args.lang.clone().or_else(|| config_file.language_for(path));
+
+    config
+}
+
+/// Resolve delimiter pairs for the file.
+/// Delimiters given by the command line take precedence over `--lang`, and `--lang` takes precedence over the config file.
+/// If several pairs are given by the command line, they are used as they are.
+fn resolve_delimiters(
+    args: &Args,
+    config_file: &config::ConfigFile,
+    path: Option<&Path>,
+) -> Vec<(String, String)> {
+    let delimiter_end = delimiter_end_args(args);
+    if args.delimiter_start.len() > 1 || delimiter_end.len() > 1 {
+        return args
+            .delimiter_start
+            .iter()
+            .cloned()
+            .zip(delimiter_end)
+            .collect();
+    }
+
+    let mut pairs = match args
+        .lang
+        .as_ref()
+        .and_then(|lang| config_file.language_delimiters(lang))
+    {
+        Some(pairs) => pairs
+            .into_iter()
+            .map(|(start, end)| (Some(start), Some(end)))
+            .collect(),
+        None => config_file.delimiters_for(path),
     };
+    if !args.delimiter_start.is_empty() || !delimiter_end.is_empty() {
+        // A pair given by the command line replaces the pairs of the config file.
+        // The end of a preset does not fit an overridden start, so the configured (or the default) end is used.
+        let pair = match args.delimiter_start.first() {
+            Some(start) => (
+                Some(start.clone()),
+                delimiter_end
+                    .first()
+                    .cloned()
+                    .or(config_file.delimiters.end.clone()),
+            ),
+            None => (
+                pairs.first_mut().and_then(|(start, _)| start.take()),
+                delimiter_end.first().cloned(),
+            ),
+        };
+        pairs = vec![pair];
+    }
 
-    let content = Rc::new(content);
+    pairs
+        .into_iter()
+        .map(|(start, end)| {
+            (
+                start.unwrap_or(DEFAULT_DELIMITER_START.to_string()),
+                end.unwrap_or(DEFAULT_DELIMITER_END.to_string()),
+            )
+        })
+        .collect()
+}
 
-    let output = if args.list {
-        list(
-            content,
-            (args.delimiter_start, args.delimiter_end),
-            config,
-            convert_list_format(args.list_json),
-        )
-        .unwrap()
-    } else if args.list_all {
-        list_all(
-            content,
-            (args.delimiter_start, args.delimiter_end),
+/// End delimiters given by the command line.
+/// With `--delimiter-end-eol`, every start delimiter is paired with the end of the line.
+fn delimiter_end_args(args: &Args) -> Vec<String> {
+    if args.delimiter_end_eol {
+        vec![END_OF_LINE.to_string(); args.delimiter_start.len().max(1)]
+    } else {
+        args.delimiter_end.clone()
+    }
+}
+
+/// Check that the numbers of `--delimiter-start` and `--delimiter-end` match when several pairs are given.
+fn validate_delimiter_args(args: &Args) -> Result<(), String> {
+    let (starts, ends) = (args.delimiter_start.len(), delimiter_end_args(args).len());
+    if (starts > 1 || ends > 1) && starts != ends {
+        return Err(format!(
+            "--delimiter-start and --delimiter-end must be given the same number of times to use multiple delimiter pairs (--delimiter-start: {starts}, --delimiter-end: {ends})"
+        ));
+    }
+
+    Ok(())
+}
+
+/// The delimiter pairs and the language of a file
+type ChiritoriKey = (Vec<(String, String)>, Option<String>);
+
+/// `Chiritori` built for the delimiters and the language of each file.
+/// It is built once for all the files sharing them.
+struct ChiritoriCache<'a> {
+    args: &'a Args,
+    config_file: &'a config::ConfigFile,
+    config: ChiritoriConfiguration,
+    built: HashMap<ChiritoriKey, Chiritori>,
+}
+
+impl<'a> ChiritoriCache<'a> {
+    fn new(
+        args: &'a Args,
+        config_file: &'a config::ConfigFile,
+        config: ChiritoriConfiguration,
+    ) -> Self {
+        Self {
+            args,
+            config_file,
             config,
-            convert_list_format(args.list_json),
-        )
-        .unwrap()
+            built: HashMap::new(),
+        }
+    }
+
+    fn get(&mut self, path: Option<&Path>) -> Result<&Chiritori, CliError> {
+        let delimiters = resolve_delimiters(self.args, self.config_file, path);
+        let config = configuration_for(self.args, self.config_file, &self.config, path);
+        let key = (
+            delimiters.clone(),
+            config.removal_strategy_configuration.language.clone(),
+        );
+
+        match self.built.entry(key) {
+            Entry::Occupied(entry) => Ok(entry.into_mut()),
+            Entry::Vacant(entry) => Ok(entry.insert(build(delimiters, config)?)),
+        }
+    }
+}
+
+fn process(
+    args: &Args,
+    chiritori: &Chiritori,
+    content: &str,
+    path: Option<&Path>,
+) -> Result<String, CliError> {
+    // Elements that cannot be evaluated are found in the same pass as the output.
+    let (output, invalid_elements) = if args.list || args.list_all {
+        let format = convert_list_format(args.list_json);
+        let (output, invalid_elements) = if args.list {
+            chiritori.list_with_report(content, format)
+        } else {
+            chiritori.list_all_with_report(content, format)
+        }
+        .map_err(ChiritoriError::from)?;
+
+        let output = match path {
+            Some(path) if args.list_json => attach_file_path(&output, path)?,
+            _ => output,
+        };
+        (output, invalid_elements)
     } else {
-        clean(content, (args.delimiter_start, args.delimiter_end), config)
+        let (cleaned, invalid_elements) = chiritori.clean_with_report(content);
+        let cleaned = keep_trailing_line_break(content, cleaned);
+
+        let output = if args.diff {
+            let path = path.map_or("stdin".to_string(), |p| p.to_string_lossy().to_string());
+            build_unified_diff(content, &cleaned, &path)
+        } else {
+            cleaned
+        };
+        (output, invalid_elements)
+    };
+
+    if report_invalid_elements(args, path, content, &invalid_elements) && args.strict {
+        return Err(CliError::InvalidElements);
+    }
+
+    Ok(output)
+}
+
+/// Add the path of the file to each item of the JSON list.
+fn attach_file_path(output: &str, path: &Path) -> Result<String, CliError> {
+    let mut items: serde_json::Value = serde_json::from_str(output)?;
+    for item in items.as_array_mut().into_iter().flatten() {
+        if let Some(item) = item.as_object_mut() {
+            item.insert(
+                "file_path".to_string(),
+                serde_json::Value::String(path.display().to_string()),
+            );
+        }
+    }
+
+    Ok(serde_json::to_string(&items)?)
+}
+
+/// Print elements that cannot be evaluated. Returns true if there is any.
+fn report_invalid_elements(
+    args: &Args,
+    path: Option<&Path>,
+    content: &str,
+    invalid_elements: &[InvalidElement],
+) -> bool {
+    let severity = if args.strict {
+        Severity::Error
+    } else {
+        Severity::Warning
     };
+    let path = path.map_or("stdin".to_string(), |p| p.display().to_string());
+    let line_map = build_line_map(content);
+
+    for invalid_element in invalid_elements {
+        let diagnostic = invalid_element
+            .to_diagnostic(severity, content, &line_map)
+            .with_file(&path);
+        eprintln!("{}", format_diagnostic(args, &diagnostic, content));
+    }
+
+    !invalid_elements.is_empty()
+}
+
+fn process_paths(args: &Args, chiritori: &mut ChiritoriCache) -> Result<Vec<u8>, CliError> {
+    let files = collect_source_files(args, &args.paths)?;
+    let is_list = args.list || args.list_all;
+
+    let results: Vec<_> = files
+        .iter()
+        .map(|path| {
+            let Some((content, encoding)) = read_source_file(args, path)? else {
+                return Ok(None);
+            };
+            let output = process(args, chiritori.get(Some(path))?, &content, Some(path))
+                .and_then(|output| encode_output(args, output, encoding, Some(path)));
+
+            Ok(Some((path, output)))
+        })
+        .collect::<Result<Vec<_>, CliError>>()?
+        .into_iter()
+        .flatten()
+        .collect();
+
+    // Invalid elements of all the files have been reported in the strict mode.
+    let mut results = results
+        .into_iter()
+        .map(|(path, output)| output.map(|output| (path, output)))
+        .collect::<Result<Vec<_>, CliError>>()?
+        .into_iter();
+
+    // A single file is output in the same way as `--filename`.
+    if args.paths.len() == 1 && Path::new(&args.paths[0]).is_file() {
+        return Ok(results.next().map(|(_, output)| output).unwrap_or_default());
+    }
+
+    // Diffs of the files are concatenated into one patch.
+    if args.diff {
+        return Ok(results.flat_map(|(_, output)| output).collect());
+    }
+
+    if is_list && args.list_json {
+        let groups = results
+            .map(|(path, output)| {
+                let items: serde_json::Value = serde_json::from_slice(&output)?;
+                Ok(serde_json::json!({ "path": path, "items": items }))
+            })
+            .collect::<Result<Vec<_>, CliError>>()?
+            .into_iter()
+            .filter(|group| group["items"].as_array().is_some_and(|v| !v.is_empty()))
+            .collect::<Vec<_>>();
+
+        return Ok(serde_json::to_vec(&groups)?);
+    }
+
+    // Each file is output in its own encoding.
+    let outputs: Vec<_> = results
+        // Skip the files that have no items to be listed.
+        .filter(|(_, output)| !is_list || !output.trim_ascii().is_empty())
+        .map(|(path, output)| {
+            [format!("==> {} <==\n", path.display()).into_bytes(), output].concat()
+        })
+        .collect();
+
+    Ok(outputs.join(&b'\n'))
+}
+
+/// Print source code ready to be removed, and return the exit code.
+fn run_check(args: &Args, chiritori: &mut ChiritoriCache) -> Result<i32, CliError> {
+    let inputs = read_inputs(args, &args.paths)?;
+
+    let mut removal_count = 0;
+    let mut file_count = 0;
+    let mut has_invalid_elements = false;
+    for (path, content) in inputs {
+        let result = chiritori
+            .get(path.as_deref())?
+            .check(&content, ListFormat::PrettyString)
+            .map_err(ChiritoriError::from)?;
+        has_invalid_elements |=
+            report_invalid_elements(args, path.as_deref(), &content, &result.invalid_elements);
+
+        if result.removal_count == 0 {
+            continue;
+        }
+
+        removal_count += result.removal_count;
+        file_count += 1;
+        match path {
+            Some(path) if !args.paths.is_empty() => {
+                print!("==> {} <==\n{}", path.display(), result.report)
+            }
+            _ => print!("{}", result.report),
+        }
+    }
+
+    if has_invalid_elements && args.strict {
+        Err(CliError::InvalidElements)
+    } else if removal_count == 0 {
+        eprintln!("No source code is ready to be removed.");
+        Ok(0)
+    } else {
+        eprintln!(
+            "{} element(s) in {} file(s) are ready to be removed.",
+            removal_count, file_count
+        );
+        Ok(EXIT_CODE_REMOVALS_PENDING)
+    }
+}
+
+/// Print problems of the tag structure, and return the exit code.
+fn run_lint(
+    args: &Args,
+    paths: &[String],
+    chiritori: &mut ChiritoriCache,
+) -> Result<i32, CliError> {
+    let inputs = read_inputs(args, paths)?;
+
+    let mut problem_count = 0;
+    for (path, content) in inputs {
+        let errors = chiritori.get(path.as_deref())?.validate(&content);
+        let path = path.map_or("stdin".to_string(), |p| p.display().to_string());
+        let line_map = build_line_map(&content);
 
-    if let Some(filename) = args.output {
-        let mut f = File::create(filename).expect("file not found");
-        f.write_all(output.as_bytes())
-            .expect("something went wrong writing the file");
+        for error in &errors {
+            let diagnostic = error.to_diagnostic(&content, &line_map).with_file(&path);
+            println!("{}", format_diagnostic(args, &diagnostic, &content));
+        }
+        problem_count += errors.len();
+    }
+
+    if problem_count == 0 {
+        eprintln!("No problems found.");
+        Ok(0)
     } else {
-        print!("{}", output);
+        eprintln!("{} problem(s) found.", problem_count);
+        Ok(EXIT_CODE_LINT_PROBLEMS)
     }
 }
 
-fn load_removal_marker_target_names(filename: String) -> Vec<String> {
-    let f = File::open(filename).expect("file not found");
+fn rewrite_in_place(args: &Args, chiritori: &mut ChiritoriCache) -> Result<(), CliError> {
+    let files = match &args.filename {
+        Some(filename) => vec![PathBuf::from(filename)],
+        None if !args.paths.is_empty() => collect_source_files(args, &args.paths)?,
+        None => {
+            return Err(CliError::Usage(
+                "--in-place requires input files. More information: --help".to_string(),
+            ));
+        }
+    };
+
+    let mut has_invalid_elements = false;
+    for path in files {
+        let Some((content, encoding)) = read_source_file(args, &path)? else {
+            continue;
+        };
+        // In the strict mode, files containing invalid elements are not rewritten.
+        let output = match process(args, chiritori.get(Some(&path))?, &content, Some(&path)) {
+            Ok(output) => output,
+            Err(CliError::InvalidElements) => {
+                has_invalid_elements = true;
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+
+        // The source is compared in the same encoding to leave unchanged files untouched.
+        let original = encode_output(args, content.to_string(), encoding, Some(&path))?;
+        let output = encode_output(args, output, encoding, Some(&path))?;
+        writer::write_in_place(&path, &original, &output, args.backup)
+            .map_err(|e| CliError::io(path.display().to_string(), e))?;
+    }
+
+    if has_invalid_elements {
+        return Err(CliError::InvalidElements);
+    }
+
+    Ok(())
+}
+
+/// Read the source code from the paths, or from `--filename` / stdin if no path is given.
+fn read_inputs(args: &Args, paths: &[String]) -> Result<Vec<(Option<PathBuf>, String)>, CliError> {
+    if paths.is_empty() {
+        return Ok(vec![(
+            args.filename.as_ref().map(PathBuf::from),
+            read_single_input(args)?.0,
+        )]);
+    }
+
+    let mut inputs = vec![];
+    for path in collect_source_files(args, paths)? {
+        if let Some((content, _)) = read_source_file(args, &path)? {
+            inputs.push((Some(path), content));
+        }
+    }
+
+    Ok(inputs)
+}
+
+fn collect_source_files(args: &Args, paths: &[String]) -> Result<Vec<PathBuf>, CliError> {
+    let options = source::WalkOptions {
+        includes: &args.include,
+        excludes: &args.exclude,
+        respect_ignore_files: !args.no_ignore,
+    };
+
+    source::collect_files(paths, &options).map_err(CliError::Usage)
+}
+
+/// Read a source file and decode it. Binary files and files that cannot be decoded are skipped.
+fn read_source_file(args: &Args, path: &Path) -> Result<Option<(String, TextEncoding)>, CliError> {
+    let bytes = std::fs::read(path).map_err(|e| CliError::io(path.display().to_string(), e))?;
+
+    let encoding = TextEncoding::detect(&bytes, args.encoding);
+    if !encoding.is_utf16() && source::is_binary(&bytes) {
+        return Ok(None);
+    }
+
+    match encoding.decode(&bytes) {
+        Some(content) => Ok(Some((content, encoding))),
+        None => {
+            let diagnostic = Diagnostic::warning(
+                "invalid-encoding",
+                format!("Skipped because it is not {} text", encoding.name()),
+            )
+            .with_file(path.display().to_string());
+            eprintln!("{}", format_diagnostic(args, &diagnostic, ""));
+            Ok(None)
+        }
+    }
+}
+
+/// Encode the processed source code in the encoding of the source.
+/// Lists are reports rather than source code, so they are output in UTF-8.
+/// Diffs are output without the byte order mark, which is not a part of the lines.
+fn encode_output(
+    args: &Args,
+    output: String,
+    encoding: TextEncoding,
+    path: Option<&Path>,
+) -> Result<Vec<u8>, CliError> {
+    if args.list || args.list_all {
+        return Ok(output.into_bytes());
+    }
+
+    let encoding = match args.diff {
+        true => encoding.without_bom(),
+        false => encoding,
+    };
+
+    encoding
+        .encode(&output)
+        .ok_or_else(|| CliError::Unencodable {
+            path: path.map_or("stdin".to_string(), |p| p.display().to_string()),
+            encoding: encoding.name(),
+        })
+}
+
+/// Restore the state of the trailing line break of the original content,
+/// which is changed when an element at the end of the content is removed.
+///
+/// ```text
+///  original: a\n<tag>b</tag>   ->  cleaned: a\n  ->  a
+///  original: a <tag>b</tag>\n  ->  cleaned: a    ->  a \n
+/// ```
+fn keep_trailing_line_break(original: &str, mut cleaned: String) -> String {
+    let line_break = if original.ends_with("\r\n") {
+        "\r\n"
+    } else if original.ends_with('\n') {
+        "\n"
+    } else {
+        ""
+    };
+
+    match (line_break.is_empty(), cleaned.ends_with('\n')) {
+        // Nothing remains, so there is no line to end.
+        _ if cleaned.is_empty() => {}
+        (false, false) => cleaned.push_str(line_break),
+        (true, true) => {
+            cleaned.pop();
+            if cleaned.ends_with('\r') {
+                cleaned.pop();
+            }
+        }
+        _ => {}
+    }
+
+    cleaned
+}
+
+fn load_removal_marker_target_names(filename: &str) -> Result<Vec<String>, CliError> {
+    let f = File::open(filename).map_err(|e| CliError::io(filename, e))?;
     let reader = BufReader::new(f);
 
-    reader.lines().map_while(Result::ok).collect::<Vec<_>>()
+    reader
+        .lines()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| CliError::io(filename, e))
+}
+
+/// Format a diagnostic with `--message-format`. `content` is the source code the diagnostic points to.
+fn format_diagnostic(args: &Args, diagnostic: &Diagnostic, content: &str) -> String {
+    match args.message_format {
+        // An empty line separates the messages.
+        MessageFormat::Human => format!("{}\n", diagnostic.render(content)),
+        MessageFormat::Short => diagnostic.render_short(),
+        MessageFormat::Json => diagnostic.render_json(),
+    }
 }
 
 fn convert_list_format(list_json: bool) -> ListFormat {
@@ -156,3 +953,67 @@ fn convert_list_format(list_json: bool) -> ListFormat {
         false => ListFormat::PrettyString,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("a\n<tag>b</tag>", "a\n", "a")]
+    #[case("a\n<tag>b</tag>\n", "a\n", "a\n")]
+    #[case("a <tag>b</tag>\n", "a ", "a \n")]
+    #[case("a <tag>b</tag>\r\n", "a ", "a \r\n")]
+    #[case("<tag>b</tag>\n", "", "")]
+    fn test_keep_trailing_line_break(
+        #[case] original: &str,
+        #[case] cleaned: &str,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(
+            keep_trailing_line_break(original, cleaned.to_string()),
+            expected
+        );
+    }
+
+    #[rstest]
+    // The end of the preset (the end of the line) is not paired with the overridden start.
+    #[case(&["--delimiter-start", "<!-- <"], "", &[("<!-- <", "> -->")])]
+    #[case(&["--delimiter-start", "/* <"], "[delimiters]\nend = \"> */\"", &[("/* <", "> */")])]
+    #[case(&["--delimiter-start", "/* <", "--delimiter-end", "> */"], "", &[("/* <", "> */")])]
+    // The start of the preset is kept when only the end is given.
+    #[case(&["--delimiter-end-eol"], "", &[("# <", "\n")])]
+    #[case(&[], "", &[("# <", "\n")])]
+    fn test_resolve_delimiters(
+        #[case] args: &[&str],
+        #[case] config_file: &str,
+        #[case] expected: &[(&str, &str)],
+    ) {
+        let args = Args::parse_from(["chiritori"].iter().chain(args));
+        let config_file = config::parse(config_file).unwrap();
+        let expected: Vec<_> = expected
+            .iter()
+            .map(|(start, end)| (start.to_string(), end.to_string()))
+            .collect();
+
+        assert_eq!(
+            resolve_delimiters(&args, &config_file, Some(Path::new("main.py"))),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_chiritori_cache() {
+        let args = Args::parse_from(["chiritori"]);
+        let config_file = config::ConfigFile::default();
+        let config = build_configuration(&args, &config_file).unwrap();
+        let mut chiritori = ChiritoriCache::new(&args, &config_file, config);
+
+        // Files sharing the delimiters and the language share `Chiritori`.
+        for path in ["a.js", "b.js", "c.py", "d.js"] {
+            chiritori.get(Some(Path::new(path))).unwrap();
+        }
+
+        assert_eq!(chiritori.built.len(), 2);
+    }
+}
@@ -0,0 +1,179 @@
+use ignore::{overrides::OverrideBuilder, WalkBuilder};
+use std::path::{Path, PathBuf};
+
+/// The number of leading bytes inspected when detecting binary files.
+const BINARY_DETECTION_LEN: usize = 8000;
+
+pub struct WalkOptions<'a> {
+    pub includes: &'a [String],
+    pub excludes: &'a [String],
+    pub respect_ignore_files: bool,
+}
+
+/// Collect the files to be processed from the given paths.
+///
+/// Files given explicitly are always returned. Directories are walked recursively,
+/// and the files in them are filtered by the include / exclude globs and `.gitignore`.
+pub fn collect_files(paths: &[String], options: &WalkOptions) -> Result<Vec<PathBuf>, String> {
+    let mut files = vec![];
+
+    for path in paths {
+        let path = Path::new(path);
+
+        if !path.exists() {
+            return Err(format!("{}: No such file or directory", path.display()));
+        }
+
+        if path.is_dir() {
+            files.extend(walk(path, options)?);
+        } else {
+            files.push(path.to_path_buf());
+        }
+    }
+
+    Ok(files)
+}
+
+fn walk(dir: &Path, options: &WalkOptions) -> Result<Vec<PathBuf>, String> {
+    let mut overrides = OverrideBuilder::new(dir);
+    for include in options.includes {
+        overrides
+            .add(include)
+            .map_err(|e| format!("Invalid include pattern: {}", e))?;
+    }
+    for exclude in options.excludes {
+        overrides
+            .add(&format!("!{}", exclude))
+            .map_err(|e| format!("Invalid exclude pattern: {}", e))?;
+    }
+    let overrides = overrides
+        .build()
+        .map_err(|e| format!("Invalid glob pattern: {}", e))?;
+
+    let mut files = vec![];
+    let walker = WalkBuilder::new(dir)
+        .standard_filters(options.respect_ignore_files)
+        .require_git(false)
+        .overrides(overrides)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .build();
+
+    for entry in walker {
+        let entry = entry.map_err(|e| e.to_string())?;
+        if entry.file_type().is_some_and(|t| t.is_file()) {
+            files.push(entry.into_path());
+        }
+    }
+
+    Ok(files)
+}
+
+/// Returns true if the content seems to be a binary file (contains NUL bytes).
+pub fn is_binary(bytes: &[u8]) -> bool {
+    bytes[..bytes.len().min(BINARY_DETECTION_LEN)].contains(&0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn create_tree() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src/nested")).unwrap();
+        fs::create_dir_all(root.join("node_modules/lib")).unwrap();
+        fs::write(root.join("src/a.js"), "a").unwrap();
+        fs::write(root.join("src/b.html"), "b").unwrap();
+        fs::write(root.join("src/nested/c.js"), "c").unwrap();
+        fs::write(root.join("src/ignored.js"), "d").unwrap();
+        fs::write(root.join("node_modules/lib/d.js"), "d").unwrap();
+        fs::write(root.join(".gitignore"), "ignored.js\n").unwrap();
+
+        dir
+    }
+
+    fn relative_paths(root: &Path, files: Vec<PathBuf>) -> Vec<String> {
+        files
+            .into_iter()
+            .map(|f| {
+                f.strip_prefix(root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_collect_files() {
+        let dir = create_tree();
+        let root = dir.path();
+        let options = WalkOptions {
+            includes: &[],
+            excludes: &["node_modules".to_string()],
+            respect_ignore_files: true,
+        };
+
+        let files = collect_files(&[root.to_string_lossy().to_string()], &options).unwrap();
+        assert_eq!(
+            relative_paths(root, files),
+            vec!["src/a.js", "src/b.html", "src/nested/c.js"]
+        );
+    }
+
+    #[test]
+    fn test_collect_files_with_include() {
+        let dir = create_tree();
+        let root = dir.path();
+        let options = WalkOptions {
+            includes: &["*.js".to_string()],
+            excludes: &[],
+            respect_ignore_files: false,
+        };
+
+        let files = collect_files(&[root.to_string_lossy().to_string()], &options).unwrap();
+        assert_eq!(
+            relative_paths(root, files),
+            vec![
+                "node_modules/lib/d.js",
+                "src/a.js",
+                "src/ignored.js",
+                "src/nested/c.js"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_collect_files_with_explicit_file() {
+        let dir = create_tree();
+        let root = dir.path();
+        let options = WalkOptions {
+            includes: &["*.html".to_string()],
+            excludes: &[],
+            respect_ignore_files: true,
+        };
+        let file = root.join("src/ignored.js").to_string_lossy().to_string();
+
+        let files = collect_files(&[file], &options).unwrap();
+        assert_eq!(relative_paths(root, files), vec!["src/ignored.js"]);
+    }
+
+    #[test]
+    fn test_collect_files_not_found() {
+        let options = WalkOptions {
+            includes: &[],
+            excludes: &[],
+            respect_ignore_files: true,
+        };
+
+        assert!(collect_files(&["./not-found-path".to_string()], &options).is_err());
+    }
+
+    #[test]
+    fn test_is_binary() {
+        assert!(is_binary(b"foo\0bar"));
+        assert!(!is_binary("foo bar あ".as_bytes()));
+        assert!(!is_binary(b""));
+    }
+}
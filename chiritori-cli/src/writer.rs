@@ -0,0 +1,105 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use tempfile::NamedTempFile;
+
+const BACKUP_SUFFIX: &str = ".orig";
+
+/// Rewrite a file atomically with the new content.
+///
+/// The content is written to a temporary file in the same directory and renamed to the original path,
+/// so the file is never left half-written. The permissions of the original file are kept.
+/// If the content is unchanged, the file is not touched at all.
+///
+/// Returns true if the file is rewritten.
+pub fn write_in_place(
+    path: &Path,
+    original: &[u8],
+    content: &[u8],
+    backup: bool,
+) -> io::Result<bool> {
+    if original == content {
+        return Ok(false);
+    }
+
+    let permissions = fs::metadata(path)?.permissions();
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    let mut temp_file = NamedTempFile::new_in(dir)?;
+    temp_file.write_all(content)?;
+    temp_file.as_file().sync_all()?;
+    fs::set_permissions(temp_file.path(), permissions)?;
+
+    if backup {
+        let mut backup_path = path.as_os_str().to_owned();
+        backup_path.push(BACKUP_SUFFIX);
+        fs::copy(path, backup_path)?;
+    }
+
+    temp_file.persist(path).map_err(|e| e.error)?;
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_in_place() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.js");
+        fs::write(&path, "foo").unwrap();
+
+        assert!(write_in_place(&path, b"foo", b"bar", false).unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "bar");
+        assert!(!dir.path().join("a.js.orig").exists());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_write_in_place_with_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.js");
+        fs::write(&path, "foo").unwrap();
+
+        assert!(write_in_place(&path, b"foo", b"bar", true).unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "bar");
+        assert_eq!(
+            fs::read_to_string(dir.path().join("a.js.orig")).unwrap(),
+            "foo"
+        );
+    }
+
+    #[test]
+    fn test_write_in_place_unchanged() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.js");
+        fs::write(&path, "foo").unwrap();
+        let modified = fs::metadata(&path).unwrap().modified().unwrap();
+
+        assert!(!write_in_place(&path, b"foo", b"foo", true).unwrap());
+        assert_eq!(fs::metadata(&path).unwrap().modified().unwrap(), modified);
+        assert!(!dir.path().join("a.js.orig").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_in_place_keeps_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.sh");
+        fs::write(&path, "foo").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+
+        write_in_place(&path, b"foo", b"bar", false).unwrap();
+        assert_eq!(
+            fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+            0o755
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use chiritori::chiritori::{
-    ChiritoriConfiguration, RemovalMarkerConfiguration, TimeLimitedConfiguration, ListFormat
+    ChiritoriConfiguration, RemovalMarkerConfiguration, RemovalStrategyConfiguration,
+    TimeLimitedConfiguration, TimeReleasedConfiguration, ListFormat
 };
 use serde::{Deserialize, Serialize};
 use std::{collections::HashSet, rc::Rc};
@@ -17,7 +18,9 @@ impl From<WasmChiritoriConfiguration> for ChiritoriConfiguration {
     fn from(val: WasmChiritoriConfiguration) -> Self {
         ChiritoriConfiguration {
             time_limited_configuration: val.time_limited_configuration.into(),
+            time_released_configuration: TimeReleasedConfiguration::default(),
             removal_marker_configuration: val.removal_marker_configuration.into(),
+            removal_strategy_configuration: RemovalStrategyConfiguration::default(),
         }
     }
 }
@@ -65,10 +68,11 @@ pub fn list_all(
     delimiter_start: String,
     delimiter_end: String,
     config: WasmChiritoriConfiguration,
-) -> String {
+) -> Result<String, JsError> {
     let content = Rc::new(content);
 
-    chiritori::chiritori::list_all(content, (delimiter_start, delimiter_end), config.into(), ListFormat::PrettyString).unwrap()
+    chiritori::chiritori::list_all(content, vec![(delimiter_start, delimiter_end)], config.into(), ListFormat::PrettyString)
+        .map_err(|e| JsError::new(&e.to_string()))
 }
 
 #[wasm_bindgen]
@@ -77,8 +81,9 @@ pub fn clean(
     delimiter_start: String,
     delimiter_end: String,
     config: WasmChiritoriConfiguration,
-) -> String {
+) -> Result<String, JsError> {
     let content = Rc::new(content);
 
-    chiritori::chiritori::clean(content, (delimiter_start, delimiter_end), config.into())
+    chiritori::chiritori::clean(content, vec![(delimiter_start, delimiter_end)], config.into())
+        .map_err(|e| JsError::new(&e.to_string()))
 }
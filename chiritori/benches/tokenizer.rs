@@ -0,0 +1,40 @@
+use chiritori::tokenizer::tokenize;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::hint::black_box;
+
+const FIXTURE: &str = include_str!("../src/integration-test-fixtures/test001.input.js");
+
+/// Builds an input of at least `size` bytes by repeating the fixture like a generated bundle.
+fn build_source(size: usize) -> String {
+    FIXTURE.repeat(size / FIXTURE.len() + 1)
+}
+
+/// Builds an input containing many near-miss delimiters that only match after backtracking.
+fn build_near_miss_source(size: usize) -> String {
+    "/* <!<!-- < time-limited to=\"2001-01-01\" --->\nconst a = '--';\n/* < /time-limited --->\n"
+        .repeat(size / 80 + 1)
+}
+
+fn bench_tokenize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tokenize");
+    group.sample_size(20);
+
+    for size in [1 << 20, 8 << 20] {
+        let source = build_source(size);
+        group.throughput(Throughput::Bytes(source.len() as u64));
+        group.bench_with_input(BenchmarkId::new("bundle", size), &source, |b, source| {
+            b.iter(|| tokenize(black_box(source), "/* <", "> */"))
+        });
+
+        let source = build_near_miss_source(size);
+        group.throughput(Throughput::Bytes(source.len() as u64));
+        group.bench_with_input(BenchmarkId::new("near_miss", size), &source, |b, source| {
+            b.iter(|| tokenize(black_box(source), "<!--", "-->"))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_tokenize);
+criterion_main!(benches);
@@ -1,174 +1,265 @@
-use crate::{
-    code::{
-        formatter::{self, BlockFormatter, Formatter},
-        list::{build_list, build_pretty_string},
-        remover::{
-            self,
-            marker::{
-                availability::{
-                    range_marker_availability::RangeMarkerAvailability,
-                    unwrap_block_marker_availability::UnwrapBlockMarkerAvailability,
-                },
-                builder::{
-                    range_marker_builder::RangeMarkerBuilder,
-                    unwrap_block_marker_builder::UnwrapBlockMarkerBuilder,
-                },
-                factory::RemoveStrategies,
-            },
-            removal_evaluator::RemovalEvaluator,
-            Remover,
-        },
-        utils::line_map::build_line_map,
-    },
-    parser, tokenizer,
-};
+pub mod builder;
+
+use crate::code::remover::InvalidElement;
+use crate::code::utils::datetime_parser::{parse_time_offset, DateTimeParseError};
+use crate::validator::ValidationError;
+use builder::ChiritoriBuilder;
 use std::{
     collections::{HashMap, HashSet},
     rc::Rc,
+    str::FromStr,
 };
 use thiserror::Error;
 
+#[derive(Clone)]
 pub struct ChiritoriConfiguration {
     pub time_limited_configuration: TimeLimitedConfiguration,
+    pub time_released_configuration: TimeReleasedConfiguration,
     pub removal_marker_configuration: RemovalMarkerConfiguration,
+    pub removal_strategy_configuration: RemovalStrategyConfiguration,
 }
 
+#[derive(Clone)]
 pub struct TimeLimitedConfiguration {
     pub tag_name: String,
     pub time_offset: String,
     pub current: chrono::DateTime<chrono::Local>,
 }
 
+/// The configuration of time-released content.
+/// The current time and the time offset of `TimeLimitedConfiguration` are shared.
+#[derive(Clone)]
+pub struct TimeReleasedConfiguration {
+    pub tag_name: String,
+    pub before_release: BeforeRelease,
+}
+
+impl Default for TimeReleasedConfiguration {
+    fn default() -> Self {
+        Self {
+            tag_name: String::from("time-released"),
+            before_release: BeforeRelease::default(),
+        }
+    }
+}
+
+/// What to do with time-released content before the release date
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BeforeRelease {
+    /// Leave the block (the tags and the content) as it is
+    #[default]
+    Keep,
+    /// Remove the whole block (e.g. for production builds)
+    Remove,
+}
+
+impl FromStr for BeforeRelease {
+    type Err = UnknownBeforeReleaseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "keep" => Ok(BeforeRelease::Keep),
+            "remove" => Ok(BeforeRelease::Remove),
+            _ => Err(UnknownBeforeReleaseError(s.to_string())),
+        }
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+#[error("Unknown action before release: {0} (expected \"keep\" or \"remove\")")]
+pub struct UnknownBeforeReleaseError(String);
+
+#[derive(Clone)]
 pub struct RemovalMarkerConfiguration {
     pub tag_name: String,
     pub targets: HashSet<String>,
 }
 
+#[derive(Clone)]
+pub struct RemovalStrategyConfiguration {
+    pub enabled_strategies: HashSet<RemovalStrategy>,
+    /// The strategy used for all elements of the tag (tag name -> strategy).
+    /// It takes precedence over the attributes choosing a strategy (e.g. `unwrap-block`).
+    pub tag_strategies: HashMap<String, RemovalStrategy>,
+    /// The language of the source code (the name of the language preset, e.g. `js`).
+    /// With the `tree-sitter` feature, `unwrap-block` uses the syntax tree of the language if it is supported.
+    pub language: Option<String>,
+}
+
+impl Default for RemovalStrategyConfiguration {
+    fn default() -> Self {
+        Self {
+            enabled_strategies: HashSet::from([
+                RemovalStrategy::Range,
+                RemovalStrategy::UnwrapBlock,
+                RemovalStrategy::UnwrapElse,
+                RemovalStrategy::UnwrapIndent,
+                RemovalStrategy::StripTags,
+            ]),
+            tag_strategies: HashMap::new(),
+            language: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RemovalStrategy {
+    Range,
+    UnwrapBlock,
+    UnwrapElse,
+    UnwrapIndent,
+    StripTags,
+}
+
+impl FromStr for RemovalStrategy {
+    type Err = UnknownRemovalStrategyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "range" => Ok(RemovalStrategy::Range),
+            "unwrap-block" => Ok(RemovalStrategy::UnwrapBlock),
+            "unwrap-else" => Ok(RemovalStrategy::UnwrapElse),
+            "unwrap-indent" => Ok(RemovalStrategy::UnwrapIndent),
+            "strip-tags" => Ok(RemovalStrategy::StripTags),
+            _ => Err(UnknownRemovalStrategyError(s.to_string())),
+        }
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+#[error("Unknown removal strategy: {0}")]
+pub struct UnknownRemovalStrategyError(String);
+
 pub enum ListFormat {
     PrettyString,
     JSON,
 }
 
+pub struct CheckResult {
+    /// The number of elements ready to be removed
+    pub removal_count: usize,
+    /// The list of elements ready to be removed
+    pub report: String,
+    /// Elements whose removal cannot be evaluated
+    pub invalid_elements: Vec<InvalidElement>,
+}
+
 #[derive(Error, Debug)]
 pub enum ListError {
     #[error("Failed to serialize JSON.")]
     JSONSerializeError,
 }
 
+/// An error of the library entry points (`clean`, `list`, ...).
+#[derive(Error, Debug)]
+pub enum ChiritoriError {
+    #[error("No delimiter pair is given")]
+    NoDelimiters,
+    #[error("The delimiters must not be empty (start: \"{start}\", end: \"{end}\")")]
+    EmptyDelimiter { start: String, end: String },
+    #[error(transparent)]
+    InvalidTimeOffset(#[from] DateTimeParseError),
+    #[error(transparent)]
+    List(#[from] ListError),
+}
+
+/// Remove elements ready to be removed.
+/// `delimiters` is a set of delimiter pairs, and a tag opened with one pair is only closed with the same pair.
 pub fn clean(
     content: Rc<String>,
-    delimiters: (String, String),
+    delimiters: Vec<(String, String)>,
     config: ChiritoriConfiguration,
-) -> String {
-    let (delimiter_start, delimiter_end) = delimiters;
-    let tokens = tokenizer::tokenize(&content, &delimiter_start, &delimiter_end);
-
-    let parsed = parser::parse(&tokens);
-    let remover = build_remover(config, content.clone());
-    let (removed, markers) = remover.remove(parsed, &content);
-
-    let removed_pos = remover::get_removed_pos(&markers);
-    let formatter = build_formatters();
-    let structure_formatters: Vec<Box<dyn BlockFormatter>> = vec![Box::new(
-        formatter::block_indent_remover::BlockIndentRemover {},
-    )];
+) -> Result<String, ChiritoriError> {
+    Ok(build(delimiters, config)?.clean(&content))
+}
 
-    formatter::format(&removed, &removed_pos, &formatter, &structure_formatters)
+/// Build a unified diff of the changes made by `clean`.
+pub fn diff(
+    content: Rc<String>,
+    delimiters: Vec<(String, String)>,
+    config: ChiritoriConfiguration,
+    path: &str,
+) -> Result<String, ChiritoriError> {
+    Ok(build(delimiters, config)?.diff(&content, path))
 }
 
 pub fn list(
     content: Rc<String>,
-    delimiters: (String, String),
+    delimiters: Vec<(String, String)>,
     config: ChiritoriConfiguration,
     format: ListFormat,
-) -> Result<String, ListError> {
-    let (delimiter_start, delimiter_end) = delimiters;
-    let tokens = tokenizer::tokenize(&content, &delimiter_start, &delimiter_end);
+) -> Result<String, ChiritoriError> {
+    Ok(build(delimiters, config)?.list(&content, format)?)
+}
 
-    let parsed = parser::parse(&tokens);
-    let remover = build_remover(config, content.clone());
-    let markers: Vec<_> = remover
-        .build_remove_marker(&parsed)
-        .into_iter()
-        .map(|v| (v, true))
-        .collect();
-    let line_map = build_line_map(&content);
+/// Find elements whose removal cannot be evaluated because of missing or malformed attributes
+/// (e.g. a typo in the date of `time-limited`). `clean` leaves these elements as they are.
+pub fn find_invalid_elements(
+    content: Rc<String>,
+    delimiters: Vec<(String, String)>,
+    config: ChiritoriConfiguration,
+) -> Result<Vec<InvalidElement>, ChiritoriError> {
+    Ok(build(delimiters, config)?.find_invalid_elements(&content))
+}
 
-    match format {
-        ListFormat::PrettyString => Ok(build_pretty_string(&content, &markers, Some(&line_map))),
-        ListFormat::JSON => serde_json::to_string(&build_list(&content, &markers, Some(&line_map)))
-            .map_err(|_| ListError::JSONSerializeError),
-    }
+/// Check whether the source code contains elements ready to be removed.
+pub fn check(
+    content: Rc<String>,
+    delimiters: Vec<(String, String)>,
+    config: ChiritoriConfiguration,
+    format: ListFormat,
+) -> Result<CheckResult, ChiritoriError> {
+    Ok(build(delimiters, config)?.check(&content, format)?)
 }
 
 pub fn list_all(
     content: Rc<String>,
-    delimiters: (String, String),
+    delimiters: Vec<(String, String)>,
     config: ChiritoriConfiguration,
     format: ListFormat,
-) -> Result<String, ListError> {
-    let (delimiter_start, delimiter_end) = delimiters;
-    let tokens = tokenizer::tokenize(&content, &delimiter_start, &delimiter_end);
-
-    let parsed = parser::parse(&tokens);
-    let remover = build_remover(config, content.clone());
-    let markers = remover.build_remove_marker_all(&parsed);
-    let line_map = build_line_map(&content);
-
-    match format {
-        ListFormat::PrettyString => Ok(build_pretty_string(&content, &markers, Some(&line_map))),
-        ListFormat::JSON => serde_json::to_string(&build_list(&content, &markers, Some(&line_map)))
-            .map_err(|_| ListError::JSONSerializeError),
-    }
-}
-
-fn build_remover(config: ChiritoriConfiguration, content: Rc<String>) -> Remover {
-    let mut builder_map: HashMap<String, Box<dyn RemovalEvaluator>> = HashMap::new();
-    builder_map.insert(
-        config.time_limited_configuration.tag_name,
-        Box::new(
-            remover::removal_evaluator::time_limited_evaluator::TimeLimitedEvaluator {
-                current_time: config.time_limited_configuration.current,
-                time_offset: config.time_limited_configuration.time_offset,
-            },
-        ),
-    );
-
-    builder_map.insert(
-        config.removal_marker_configuration.tag_name,
-        Box::new(
-            remover::removal_evaluator::marker_evaluator::MarkerEvaluator {
-                marker_removal_names: config.removal_marker_configuration.targets,
-            },
-        ),
-    );
-
-    let remove_strategy_map: RemoveStrategies = vec![
-        (
-            Box::new(UnwrapBlockMarkerAvailability::new("unwrap-block")),
-            Box::new(UnwrapBlockMarkerBuilder { content }),
-        ),
-        (
-            Box::new(RangeMarkerAvailability::default()),
-            Box::new(RangeMarkerBuilder::default()),
-        ),
-    ];
+) -> Result<String, ChiritoriError> {
+    Ok(build(delimiters, config)?.list_all(&content, format)?)
+}
 
-    Remover::new(builder_map, remove_strategy_map)
+/// Validate the structure of tags and report problems with their line and column.
+/// Tags that cannot be evaluated (see `find_invalid_elements`) are also reported.
+/// Tags whose strategy finds nothing to remove (e.g. `unwrap-else` followed by `else if`) are reported as well.
+pub fn validate(
+    content: Rc<String>,
+    delimiters: Vec<(String, String)>,
+    config: ChiritoriConfiguration,
+) -> Result<Vec<ValidationError>, ChiritoriError> {
+    Ok(build(delimiters, config)?.validate(&content))
 }
 
-fn build_formatters() -> Vec<Box<dyn Formatter>> {
-    vec![
-        Box::new(formatter::indent_remover::IndentRemover {}),
-        Box::new(formatter::empty_line_remover::EmptyLineRemover {}),
-        Box::new(formatter::prev_line_break_remover::PrevLineBreakRemover {}),
-        Box::new(formatter::next_line_break_remover::NextLineBreakRemover {}),
-    ]
+/// Build `Chiritori` after checking the delimiters and the configuration,
+/// which would otherwise make every tag silently ignored or invalid.
+/// The built `Chiritori` can process any number of source files with the same delimiters and configuration.
+pub fn build(
+    delimiters: Vec<(String, String)>,
+    config: ChiritoriConfiguration,
+) -> Result<builder::Chiritori, ChiritoriError> {
+    if delimiters.is_empty() {
+        return Err(ChiritoriError::NoDelimiters);
+    }
+    if let Some((start, end)) = delimiters
+        .iter()
+        .find(|(start, end)| start.is_empty() || end.is_empty())
+    {
+        return Err(ChiritoriError::EmptyDelimiter {
+            start: start.clone(),
+            end: end.clone(),
+        });
+    }
+    parse_time_offset(&config.time_limited_configuration.time_offset)?;
+
+    Ok(ChiritoriBuilder::from_configuration(delimiters, config).build())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tokenizer;
     use chrono::Local;
     use rstest::rstest;
     use std::ffi::OsString;
@@ -182,10 +273,12 @@ mod tests {
                 current: Local::now(),
                 time_offset: String::from("+00:00"),
             },
+            time_released_configuration: TimeReleasedConfiguration::default(),
             removal_marker_configuration: RemovalMarkerConfiguration {
                 tag_name: String::from("marker"),
                 targets: HashSet::from([String::from("feature1")]),
             },
+            removal_strategy_configuration: RemovalStrategyConfiguration::default(),
         }
     }
 
@@ -232,8 +325,8 @@ mod tests {
 </html>"#,
         );
         let config = create_test_config();
-        let delimiters = (String::from("<!--"), String::from("-->"));
-        let result = clean(content.into(), delimiters, config);
+        let delimiters = vec![(String::from("<!--"), String::from("-->"))];
+        let result = clean(content.into(), delimiters, config).unwrap();
 
         assert_eq!(result, expected);
     }
@@ -284,12 +377,736 @@ console.log("Temporary code while feature2 is not released")
         );
 
         let config = create_test_config();
-        let delimiters = (String::from("/* <"), String::from("> */"));
-        let result = clean(content.into(), delimiters, config);
+        let delimiters = vec![(String::from("/* <"), String::from("> */"))];
+        let result = clean(content.into(), delimiters, config).unwrap();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_clean_with_multiple_delimiters() {
+        let content = String::from(
+            r#"
+<template>
+  <!-- <time-limited to="2021-01-01 00:00:00"> -->
+  <p>Campaign until 2021/01/01</p>
+  <!-- </time-limited> -->
+</template>
+<script>
+/* <time-limited to="2021-01-01 00:00:00"> */
+console.log("Campaign until 2021/01/01")
+/* </time-limited> */
+/* <time-limited to="2021-01-01 00:00:00"> */
+console.log("The tag is not closed with the same delimiters")
+<!-- /time-limited -->
+</script>
+"#,
+        );
+        let expected = String::from(
+            r#"
+<template>
+</template>
+<script>
+/* <time-limited to="2021-01-01 00:00:00"> */
+console.log("The tag is not closed with the same delimiters")
+<!-- /time-limited -->
+</script>
+"#,
+        );
+
+        let config = create_test_config();
+        let delimiters = vec![
+            (String::from("<!-- <"), String::from("> -->")),
+            (String::from("/* <"), String::from("> */")),
+        ];
+        let result = clean(content.into(), delimiters, config).unwrap();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_clean_with_end_of_line_delimiter() {
+        let content = String::from(
+            r#"
+def main():
+    # <time-limited to="2021-01-01 00:00:00">
+    print("Campaign until 2021/01/01")
+    # </time-limited>
+    # <time-limited to="9999-01-01 00:00:00">
+    print("Campaign until 9999/01/01")
+    # </time-limited>
+    return 0
+# <time-limited to="2021-01-01 00:00:00">
+main()
+# </time-limited>"#,
+        );
+        let expected = String::from(
+            r#"
+def main():
+    # <time-limited to="9999-01-01 00:00:00">
+    print("Campaign until 9999/01/01")
+    # </time-limited>
+    return 0
+"#,
+        );
+
+        let config = create_test_config();
+        let delimiters = vec![(String::from("# <"), String::from(tokenizer::END_OF_LINE))];
+        let result = clean(content.into(), delimiters, config).unwrap();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_clean_with_unwrap_block_and_end_of_line_delimiter() {
+        let content = String::from(
+            r#"function main() {
+  // <time-limited to="2021-01-01 00:00:00" unwrap-block>
+  if (isReleased) {
+    run();
+  }
+  // </time-limited>
+  done();
+}
+"#,
+        );
+        let expected = String::from(
+            r#"function main() {
+  run();
+  done();
+}
+"#,
+        );
+
+        let config = create_test_config();
+        let delimiters = vec![(String::from("// <"), String::from(tokenizer::END_OF_LINE))];
+        let result = clean(content.into(), delimiters, config).unwrap();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_clean_with_disabled_strategy() {
+        let content = String::from(
+            r#"
+/* <time-limited to="2021-01-01 00:00:00" unwrap-block> */
+if (isReleased) {
+  console.log("Released!")
+}
+/* </time-limited> */
+/* <time-limited to="2021-01-01 00:00:00"> */
+console.log("Temporary code until 2021/01/01")
+/* </time-limited> */
+"#,
+        );
+        let expected = String::from(
+            r#"
+/* <time-limited to="2021-01-01 00:00:00" unwrap-block> */
+if (isReleased) {
+  console.log("Released!")
+}
+/* </time-limited> */
+"#,
+        );
+
+        let mut config = create_test_config();
+        config.removal_strategy_configuration = RemovalStrategyConfiguration {
+            enabled_strategies: HashSet::from([RemovalStrategy::Range]),
+            ..Default::default()
+        };
+        let delimiters = vec![(String::from("/* <"), String::from("> */"))];
+        let result = clean(content.into(), delimiters, config).unwrap();
+
+        assert_eq!(result, expected);
+    }
+
+    #[rstest]
+    #[case(
+        "<!-- <marker name=\"feature1\" strip-tags> -->",
+        HashMap::new(),
+        "<ul>\n  <li>a</li>\n    <li>b</li>\n</ul>\n"
+    )]
+    #[case(
+        "<!-- <marker name=\"feature1\"> -->",
+        HashMap::from([(String::from("marker"), RemovalStrategy::StripTags)]),
+        "<ul>\n  <li>a</li>\n    <li>b</li>\n</ul>\n"
+    )]
+    // The configuration of the tag takes precedence over the attribute.
+    #[case(
+        "<!-- <marker name=\"feature1\" strip-tags> -->",
+        HashMap::from([(String::from("marker"), RemovalStrategy::Range)]),
+        "<ul>\n  <li>a</li>\n</ul>\n"
+    )]
+    fn test_clean_with_strip_tags(
+        #[case] start_tag: &str,
+        #[case] tag_strategies: HashMap<String, RemovalStrategy>,
+        #[case] expected: &str,
+    ) {
+        let content = format!(
+            "<ul>\n  <li>a</li>\n  {}\n    <li>b</li>\n  <!-- </marker> -->\n</ul>\n",
+            start_tag
+        );
+
+        let mut config = create_test_config();
+        config.removal_strategy_configuration.tag_strategies = tag_strategies;
+        let delimiters = vec![(String::from("<!-- <"), String::from("> -->"))];
+        let result = clean(content.into(), delimiters, config).unwrap();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_clean_with_unwrap_else() {
+        let content = String::from(
+            r#"function main() {
+  /* <time-limited to="2021-01-01 00:00:00" unwrap-else> */
+  if (isLegacy) {
+    legacy();
+  } else {
+    modern();
+    if (x) {
+      y();
+    }
+  }
+  /* </time-limited> */
+  done();
+}
+"#,
+        );
+        let expected = String::from(
+            r#"function main() {
+  modern();
+  if (x) {
+    y();
+  }
+  done();
+}
+"#,
+        );
+
+        let config = create_test_config();
+        let delimiters = vec![(String::from("/* <"), String::from("> */"))];
+        let result = clean(content.into(), delimiters, config).unwrap();
+
+        assert_eq!(result, expected);
+    }
+
+    #[rstest]
+    #[case(
+        r#"def main():
+    # <time-limited to="2021-01-01 00:00:00" unwrap-indent>
+    if is_legacy:
+        legacy()
+
+        cleanup()
+    # </time-limited>
+    return 0
+"#,
+        r#"def main():
+    legacy()
+
+    cleanup()
+    return 0
+"#
+    )]
+    #[case(
+        r#"server:
+	# <time-limited to="2021-01-01 00:00:00" unwrap-indent>
+	legacy:
+		port: 8080
+		host: localhost
+	# </time-limited>
+	name: app
+"#,
+        r#"server:
+	port: 8080
+	host: localhost
+	name: app
+"#
+    )]
+    // The tag is less indented than the header.
+    #[case(
+        r#"# <time-limited to="2021-01-01 00:00:00" unwrap-indent>
+    if is_legacy:
+        legacy()
+            .run()
+# </time-limited>
+done()
+"#,
+        r#"    legacy()
+        .run()
+done()
+"#
+    )]
+    // The tag is more indented than the header.
+    #[case(
+        r#"def main():
+        # <time-limited to="2021-01-01 00:00:00" unwrap-indent>
+    if is_legacy:
+      legacy()
+        # </time-limited>
+    return 0
+"#,
+        r#"def main():
+    legacy()
+    return 0
+"#
+    )]
+    // Nested blocks
+    #[case(
+        r#"# <time-limited to="2021-01-01 00:00:00" unwrap-indent>
+if a:
+    # <time-limited to="2021-01-01 00:00:00" unwrap-indent>
+    if b:
+        c()
+    # </time-limited>
+    d()
+# </time-limited>
+"#,
+        r#"c()
+d()
+"#
+    )]
+    // The body is indented inconsistently.
+    #[case(
+        r#"# <time-limited to="2021-01-01 00:00:00" unwrap-indent>
+if a:
+    b()
+	c()
+# </time-limited>
+"#,
+        r#"# <time-limited to="2021-01-01 00:00:00" unwrap-indent>
+if a:
+    b()
+	c()
+# </time-limited>
+"#
+    )]
+    fn test_clean_with_unwrap_indent(#[case] content: &str, #[case] expected: &str) {
+        let config = create_test_config();
+        let delimiters = vec![(String::from("# <"), String::from(tokenizer::END_OF_LINE))];
+        let result = clean(String::from(content).into(), delimiters, config).unwrap();
+
+        assert_eq!(result, expected);
+    }
+
+    #[cfg(feature = "tree-sitter")]
+    #[test]
+    fn test_clean_with_syntax_unwrap_block() {
+        let content = String::from(
+            r#"function main() {
+  // <time-limited to="2021-01-01 00:00:00" unwrap-block>
+  if (
+    isReleased &&
+    isEnabled
+  )
+  {
+    run();
+  } else {
+    legacy();
+  }
+  // </time-limited>
+  /* <time-limited to="2021-01-01 00:00:00" unwrap-block> */ if (a) { b(); } /* </time-limited> */
+  done();
+}
+"#,
+        );
+        let expected = String::from(
+            r#"function main() {
+  run();
+  b();
+  done();
+}
+"#,
+        );
+
+        let mut config = create_test_config();
+        config.removal_strategy_configuration.language = Some(String::from("js"));
+        let delimiters = vec![
+            (String::from("/* <"), String::from("> */")),
+            (String::from("// <"), String::from("\n")),
+        ];
+        let result = clean(content.into(), delimiters, config).unwrap();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_check() {
+        let content = String::from(
+            r#"
+/* <time-limited to="2021-01-01 00:00:00" unwrap-block> */
+if (isReleased) {
+  console.log("Released!")
+}
+/* </time-limited> */
+/* <time-limited to="2021-01-01 00:00:00"> */
+console.log("Temporary code until 2021/01/01")
+/* </time-limited> */
+/* <time-limited to="9999-01-01 00:00:00"> */
+console.log("Temporary code until 9999/01/01")
+/* </time-limited> */
+"#,
+        );
+
+        let config = create_test_config();
+        let delimiters = vec![(String::from("/* <"), String::from("> */"))];
+        let result = check(content.into(), delimiters, config, ListFormat::JSON).unwrap();
+
+        assert_eq!(result.removal_count, 2);
+        assert!(!result.report.contains("9999/01/01"));
+
+        let content = String::from(
+            r#"
+/* <time-limited to="9999-01-01 00:00:00"> */
+console.log("Temporary code until 9999/01/01")
+/* </time-limited> */
+"#,
+        );
+
+        let config = create_test_config();
+        let delimiters = vec![(String::from("/* <"), String::from("> */"))];
+        let result = check(content.into(), delimiters, config, ListFormat::JSON).unwrap();
+
+        assert_eq!(result.removal_count, 0);
+        assert_eq!(result.report, "[]");
+    }
+
+    #[test]
+    fn test_diff() {
+        let content = String::from(
+            r#"foo
+/* <time-limited to="2021-01-01 00:00:00"> */
+console.log("Temporary code until 2021/01/01")
+/* </time-limited> */
+bar
+"#,
+        );
+        let expected = r#"diff --git a/src/index.js b/src/index.js
+--- a/src/index.js
++++ b/src/index.js
+@@ -1,5 +1,2 @@
+ foo
+-/* <time-limited to="2021-01-01 00:00:00"> */
+-console.log("Temporary code until 2021/01/01")
+-/* </time-limited> */
+ bar
+"#;
+
+        let config = create_test_config();
+        let delimiters = vec![(String::from("/* <"), String::from("> */"))];
+        let result = diff(content.into(), delimiters, config, "src/index.js").unwrap();
 
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn test_invalid_elements() {
+        let content = String::from(
+            r#"
+/* <time-limited to="2021-13-01 00:00:00"> */
+console.log("Invalid date")
+/* </time-limited> */
+/* <time-limited to="2021-01-01 00:00:00"> */
+console.log("Temporary code until 2021/01/01")
+/* </time-limited> */
+"#,
+        );
+        let expected = String::from(
+            r#"
+/* <time-limited to="2021-13-01 00:00:00"> */
+console.log("Invalid date")
+/* </time-limited> */
+"#,
+        );
+
+        let config = create_test_config();
+        let delimiters = vec![(String::from("/* <"), String::from("> */"))];
+        let content = Rc::new(content);
+
+        let invalid_elements =
+            find_invalid_elements(content.clone(), delimiters.clone(), config.clone()).unwrap();
+        assert_eq!(invalid_elements.len(), 1);
+        assert_eq!(invalid_elements[0].tag_name, "time-limited");
+        assert_eq!(invalid_elements[0].byte_range, 1..46);
+
+        let result = clean(content.clone(), delimiters.clone(), config.clone()).unwrap();
+        assert_eq!(result, expected);
+
+        let result = list(
+            content.clone(),
+            delimiters.clone(),
+            config.clone(),
+            ListFormat::JSON,
+        )
+        .unwrap();
+        assert!(result.contains(r#""current_status":"Invalid","message":"<time-limited> The attribute \"to\" is invalid: Invalid date time: \"2021-13-01 00:00:00\"""#));
+
+        let result = check(
+            content.clone(),
+            delimiters.clone(),
+            config.clone(),
+            ListFormat::PrettyString,
+        )
+        .unwrap();
+        assert_eq!(result.removal_count, 1);
+        assert_eq!(result.invalid_elements, invalid_elements);
+
+        // The invalid elements are found in the same pass.
+        let chiritori = build(delimiters, config).unwrap();
+        assert_eq!(
+            chiritori.clean_with_report(&content),
+            (expected, invalid_elements.clone())
+        );
+        let (_, report) = chiritori
+            .list_with_report(&content, ListFormat::JSON)
+            .unwrap();
+        assert_eq!(report, invalid_elements);
+        let (_, report) = chiritori
+            .list_all_with_report(&content, ListFormat::JSON)
+            .unwrap();
+        assert_eq!(report, invalid_elements);
+    }
+
+    #[test]
+    fn test_validate() {
+        let content = String::from(
+            r#"
+/* <time-limited to="2021-13-01 00:00:00"> */
+console.log("Invalid date")
+/* </time-limited> */
+/* <time-limted to="2021-01-01 00:00:00"> */
+console.log("Typo")
+/* </time-limited> */
+/* <marker name="feature1"> */
+/* <marker name="feature2"
+"#,
+        );
+
+        let config = create_test_config();
+        let delimiters = vec![(String::from("/* <"), String::from("> */"))];
+        let errors = validate(content.into(), delimiters, config).unwrap();
+
+        assert_eq!(
+            errors.iter().map(|e| e.to_string()).collect::<Vec<_>>(),
+            vec![
+                r#"2:1: <time-limited> The attribute "to" is invalid: Invalid date time: "2021-13-01 00:00:00""#,
+                r#"5:1: Unknown tag name "time-limted". Did you mean "time-limited"?"#,
+                r#"7:1: The closing tag </time-limited> has no opening tag"#,
+                r#"8:1: The tag <marker> is not closed"#,
+                r#"9:1: The delimiter "/* <" is not closed with "> */""#,
+            ]
+        );
+        assert_eq!(
+            errors.iter().map(|e| e.kind.code()).collect::<Vec<_>>(),
+            vec![
+                "invalid-attribute-value",
+                "unknown-tag-name",
+                "orphan-closing-tag",
+                "unclosed-tag",
+                "unterminated-delimiter",
+            ]
+        );
+    }
+
+    #[rstest]
+    // Released: only the tags are removed, and the content keeps its indent.
+    #[case(
+        "2021-01-01",
+        BeforeRelease::Keep,
+        "<div>\n  <p>a</p>\n    <p>b</p>\n  <p>x c y</p>\n</div>\n"
+    )]
+    #[case(
+        "2021-01-01",
+        BeforeRelease::Remove,
+        "<div>\n  <p>a</p>\n    <p>b</p>\n  <p>x c y</p>\n</div>\n"
+    )]
+    // Not released yet
+    #[case("2999-01-01", BeforeRelease::Keep, TIME_RELEASED_CONTENT)]
+    #[case("2999-01-01", BeforeRelease::Remove, "<div>\n  <p>x  y</p>\n</div>\n")]
+    fn test_clean_time_released_code(
+        #[case] from: &str,
+        #[case] before_release: BeforeRelease,
+        #[case] expected: &str,
+    ) {
+        let mut config = create_test_config();
+        config.time_released_configuration.before_release = before_release;
+        let delimiters = vec![(String::from("<!-- <"), String::from("> -->"))];
+        let content = TIME_RELEASED_CONTENT.replace("FROM", from);
+        let result = clean(content.clone().into(), delimiters, config).unwrap();
+
+        assert_eq!(result, expected.replace("FROM", from));
+    }
+
+    #[test]
+    fn test_clean_time_released_code_with_strip_tags_disabled() {
+        let mut config = create_test_config();
+        config
+            .removal_strategy_configuration
+            .enabled_strategies
+            .remove(&RemovalStrategy::StripTags);
+        let delimiters = vec![(String::from("<!-- <"), String::from("> -->"))];
+        let content = TIME_RELEASED_CONTENT.replace("FROM", "2021-01-01");
+        let result = clean(content.into(), delimiters, config).unwrap();
+
+        assert_eq!(
+            result,
+            "<div>\n  <p>a</p>\n    <p>b</p>\n  <p>x c y</p>\n</div>\n"
+        );
+    }
+
+    #[test]
+    fn test_clean_time_released_code_with_tag_strategy() {
+        let mut config = create_test_config();
+        config
+            .removal_strategy_configuration
+            .tag_strategies
+            .insert(String::from("time-released"), RemovalStrategy::Range);
+        let delimiters = vec![(String::from("<!-- <"), String::from("> -->"))];
+        let content = TIME_RELEASED_CONTENT.replace("FROM", "2021-01-01");
+        let result = clean(content.into(), delimiters, config).unwrap();
+
+        assert_eq!(result, "<div>\n  <p>x  y</p>\n</div>\n");
+    }
+
+    const TIME_RELEASED_CONTENT: &str = r#"<div>
+  <!-- <time-released from="FROM"> -->
+  <p>a</p>
+<!-- <time-released from="FROM"> -->
+    <p>b</p>
+<!-- </time-released> -->
+  <!-- </time-released> -->
+  <p>x <!-- <time-released from="FROM"> -->c<!-- </time-released> --> y</p>
+</div>
+"#;
+
+    #[test]
+    fn test_parse_before_release() {
+        assert_eq!("keep".parse(), Ok(BeforeRelease::Keep));
+        assert_eq!("remove".parse(), Ok(BeforeRelease::Remove));
+        assert_eq!(
+            "hide".parse::<BeforeRelease>(),
+            Err(UnknownBeforeReleaseError("hide".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_validate_with_plain_comments() {
+        let content = String::from(
+            r#"<!-- hello world -->
+<!-- time-limited to="2021-01-01 00:00:00" -->
+<!-- TODO: remove this -->
+<!-- /time-limited -->
+"#,
+        );
+
+        let config = create_test_config();
+        let delimiters = vec![(String::from("<!--"), String::from("-->"))];
+        let errors = validate(content.into(), delimiters, config).unwrap();
+
+        assert_eq!(errors, vec![]);
+    }
+
+    #[test]
+    fn test_validate_with_mismatched_delimiters() {
+        let content = String::from(
+            r#"<!-- <time-limited to="2020-01-01 00:00:00"> -->
+<p>a</p>
+/* </time-limited> */
+"#,
+        );
+
+        let config = create_test_config();
+        let delimiters = vec![
+            (String::from("<!-- <"), String::from("> -->")),
+            (String::from("/* <"), String::from("> */")),
+        ];
+        let errors = validate(content.into(), delimiters, config).unwrap();
+
+        assert_eq!(
+            errors.iter().map(|e| e.to_string()).collect::<Vec<_>>(),
+            vec![
+                r#"3:1: The closing tag </time-limited> is written with "/* <", but the tag <time-limited> is opened with "<!-- <""#
+            ]
+        );
+        assert_eq!(errors[0].kind.code(), "mismatched-delimiters");
+    }
+
+    #[test]
+    fn test_validate_with_ineffective_strategy() {
+        let else_if_chain = r#"
+/* <time-limited to="2021-01-01 00:00:00" unwrap-else> */
+if (a) {
+  b();
+} else if (x) {
+  y();
+} else {
+  c();
+}
+/* </time-limited> */
+"#;
+        let content = format!(
+            "{}{}",
+            else_if_chain,
+            r#"/* <time-limited to="2021-01-01 00:00:00" unwrap-block> */
+if (a) {
+  b();
+}
+/* </time-limited> */
+"#
+        );
+
+        let config = create_test_config();
+        let delimiters = vec![(String::from("/* <"), String::from("> */"))];
+        let errors = validate(content.into(), delimiters.clone(), config.clone()).unwrap();
+
+        assert_eq!(
+            errors.iter().map(|e| e.to_string()).collect::<Vec<_>>(),
+            vec![
+                r#"2:1: <time-limited> The "unwrap-else" strategy finds nothing to remove, so the element is left as it is"#,
+            ]
+        );
+        assert_eq!(errors[0].kind.code(), "ineffective-strategy");
+
+        // The else-if branch is not removed.
+        let result = clean(String::from(else_if_chain).into(), delimiters, config).unwrap();
+        assert_eq!(result, else_if_chain);
+    }
+
+    #[rstest]
+    #[case(vec![], "+00:00", "No delimiter pair is given")]
+    #[case(
+        vec![("<!--".to_string(), String::new())],
+        "+00:00",
+        "The delimiters must not be empty (start: \"<!--\", end: \"\")"
+    )]
+    #[case(
+        vec![("<!--".to_string(), "-->".to_string())],
+        "+9:00",
+        "Invalid time offset: \"+9:00\""
+    )]
+    fn test_clean_with_invalid_configuration(
+        #[case] delimiters: Vec<(String, String)>,
+        #[case] time_offset: &str,
+        #[case] expected: &str,
+    ) {
+        let mut config = create_test_config();
+        config.time_limited_configuration.time_offset = time_offset.to_string();
+
+        let result = clean(String::from("a").into(), delimiters, config);
+        assert_eq!(result.unwrap_err().to_string(), expected);
+    }
+
+    #[test]
+    fn test_parse_removal_strategy() {
+        assert_eq!("range".parse(), Ok(RemovalStrategy::Range));
+        assert_eq!("unwrap-block".parse(), Ok(RemovalStrategy::UnwrapBlock));
+        assert_eq!("unwrap-else".parse(), Ok(RemovalStrategy::UnwrapElse));
+        assert_eq!("unwrap-indent".parse(), Ok(RemovalStrategy::UnwrapIndent));
+        assert_eq!("strip-tags".parse(), Ok(RemovalStrategy::StripTags));
+        assert!("foo".parse::<RemovalStrategy>().is_err());
+    }
+
     #[rstest]
     fn should_clean_with_js(
         #[files("src/integration-test-fixtures/*.input.js")] path_input: PathBuf,
@@ -312,8 +1129,8 @@ console.log("Temporary code while feature2 is not released")
             .expect("Failed to load an expected content file");
 
         let config = create_test_config();
-        let delimiters = (String::from("/* <"), String::from("> */"));
-        let result = clean(input_content.into(), delimiters, config);
+        let delimiters = vec![(String::from("/* <"), String::from("> */"))];
+        let result = clean(input_content.into(), delimiters, config).unwrap();
 
         assert_eq!(result, expected_content);
     }
@@ -340,7 +1157,7 @@ console.log("Temporary code while feature2 is not released")
             .expect("Failed to load an expected content file");
 
         let config = create_test_config();
-        let delimiters = (String::from("/* <"), String::from("> */"));
+        let delimiters = vec![(String::from("/* <"), String::from("> */"))];
         let result = list_all(
             input_content.into(),
             delimiters,
@@ -380,7 +1197,7 @@ console.log("Temporary code while feature2 is not released")
         expected_content.remove(expected_content.len() - 1);
 
         let config = create_test_config();
-        let delimiters = (String::from("/* <"), String::from("> */"));
+        let delimiters = vec![(String::from("/* <"), String::from("> */"))];
         let result = list(input_content.into(), delimiters, config, ListFormat::JSON).unwrap();
 
         assert_eq!(result, expected_content);
@@ -408,7 +1225,7 @@ console.log("Temporary code while feature2 is not released")
             .expect("Failed to load an expected content file");
 
         let config = create_test_config();
-        let delimiters = (String::from("/* <"), String::from("> */"));
+        let delimiters = vec![(String::from("/* <"), String::from("> */"))];
         let result = list(
             input_content.into(),
             delimiters,
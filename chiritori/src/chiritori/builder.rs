@@ -0,0 +1,655 @@
+use super::{
+    BeforeRelease, CheckResult, ChiritoriConfiguration, ListError, ListFormat, RemovalStrategy,
+    RemovalStrategyConfiguration,
+};
+#[cfg(feature = "tree-sitter")]
+use crate::code::{
+    remover::marker::builder::syntax_unwrap_block_marker_builder::SyntaxUnwrapBlockMarkerBuilder,
+    syntax::SyntaxLanguage,
+};
+use crate::{
+    code::{
+        diff::build_unified_diff,
+        formatter::{self, BlockFormatter, Formatter},
+        list::{build_list, build_pretty_string},
+        remover::{
+            self,
+            marker::{
+                availability::{
+                    attribute_marker_availability::AttributeMarkerAvailability,
+                    range_marker_availability::RangeMarkerAvailability,
+                    tag_name_marker_availability::TagNameMarkerAvailability,
+                    time_released_marker_availability::TimeReleasedMarkerAvailability,
+                    MarkerAvailability,
+                },
+                builder::{
+                    range_marker_builder::RangeMarkerBuilder,
+                    skip_marker_builder::SkipMarkerBuilder,
+                    strip_tags_marker_builder::StripTagsMarkerBuilder,
+                    unwrap_block_marker_builder::UnwrapBlockMarkerBuilder,
+                    unwrap_else_marker_builder::UnwrapElseMarkerBuilder,
+                    unwrap_indent_marker_builder::UnwrapIndentMarkerBuilder, MarkerBuilder,
+                },
+                factory::RemoveStrategies,
+            },
+            removal_evaluator::{
+                marker_evaluator::MarkerEvaluator, time_limited_evaluator::TimeLimitedEvaluator,
+                time_released_evaluator::TimeReleasedEvaluator, RemovalEvaluator,
+            },
+            InvalidElement, MarkerOrigin, RemoveMarker, Remover,
+        },
+        utils::line_map::build_line_map,
+    },
+    parser, tokenizer,
+    validator::{self, ValidationError, ValidationErrorKind},
+};
+use std::collections::{HashMap, HashSet};
+
+/// The priority of the built-in `strip-tags` strategy
+pub const STRIP_TAGS_STRATEGY_PRIORITY: i32 = 300;
+/// The priority of the built-in `unwrap-else` strategy
+pub const UNWRAP_ELSE_STRATEGY_PRIORITY: i32 = 250;
+/// The priority of the built-in `unwrap-indent` strategy
+pub const UNWRAP_INDENT_STRATEGY_PRIORITY: i32 = 220;
+/// The priority of the built-in `unwrap-block` strategy
+pub const UNWRAP_BLOCK_STRATEGY_PRIORITY: i32 = 200;
+/// The priority of the built-in `range` strategy.
+/// It is available for any element, so strategies with a lower priority are never used.
+pub const RANGE_STRATEGY_PRIORITY: i32 = 100;
+
+type PrioritizedStrategy = (i32, Box<dyn MarkerAvailability>, Box<dyn MarkerBuilder>);
+
+/// Build `Chiritori` with custom removal evaluators and strategies.
+///
+/// ```
+/// use chiritori::chiritori::builder::{ChiritoriBuilder, RANGE_STRATEGY_PRIORITY};
+/// use chiritori::code::remover::marker::{
+///     availability::range_marker_availability::RangeMarkerAvailability,
+///     builder::range_marker_builder::RangeMarkerBuilder,
+/// };
+/// use chiritori::code::remover::removal_evaluator::{EvaluationError, RemovalEvaluator};
+/// use chiritori::element_parser::Element;
+///
+/// struct DebugEvaluator {}
+///
+/// impl RemovalEvaluator for DebugEvaluator {
+///     fn is_removal(&self, _start_el: &Element) -> Result<bool, EvaluationError> {
+///         Ok(true)
+///     }
+/// }
+///
+/// let chiritori = ChiritoriBuilder::new(vec![(String::from("<!-- "), String::from(" -->"))])
+///     .removal_evaluator("debug", Box::new(DebugEvaluator {}))
+///     .remove_strategy(
+///         RANGE_STRATEGY_PRIORITY,
+///         Box::new(RangeMarkerAvailability::default()),
+///         Box::new(RangeMarkerBuilder::default()),
+///     )
+///     .build();
+///
+/// assert_eq!(chiritori.clean("a<!-- debug -->b<!-- /debug -->c"), "ac");
+/// ```
+pub struct ChiritoriBuilder {
+    delimiters: Vec<(String, String)>,
+    removal_evaluators: HashMap<String, Box<dyn RemovalEvaluator>>,
+    remove_strategies: Vec<PrioritizedStrategy>,
+}
+
+impl ChiritoriBuilder {
+    /// Create a builder without any removal evaluator and strategy.
+    ///
+    /// `delimiters` is a set of delimiter pairs processed in one pass.
+    /// A tag opened with one pair is only closed with the same pair.
+    pub fn new(delimiters: Vec<(String, String)>) -> Self {
+        Self {
+            delimiters,
+            removal_evaluators: HashMap::new(),
+            remove_strategies: vec![],
+        }
+    }
+
+    /// Create a builder with the built-in removal evaluators (`time-limited`, `time-released`, `removal-marker`)
+    /// and strategies (`strip-tags`, `unwrap-else`, `unwrap-indent`, `unwrap-block`, `range`) set up by the configuration.
+    pub fn from_configuration(
+        delimiters: Vec<(String, String)>,
+        config: ChiritoriConfiguration,
+    ) -> Self {
+        let strategy_config = &config.removal_strategy_configuration;
+        let (strip_tags_availability, strip_tags_builder) = build_strategy(
+            strategy_config,
+            RemovalStrategy::StripTags,
+            Box::new(AttributeMarkerAvailability::new("strip-tags")),
+            Box::new(StripTagsMarkerBuilder::default()),
+        );
+        let (unwrap_else_availability, unwrap_else_builder) = build_strategy(
+            strategy_config,
+            RemovalStrategy::UnwrapElse,
+            Box::new(AttributeMarkerAvailability::new("unwrap-else")),
+            Box::new(UnwrapElseMarkerBuilder::default()),
+        );
+        let (unwrap_indent_availability, unwrap_indent_builder) = build_strategy(
+            strategy_config,
+            RemovalStrategy::UnwrapIndent,
+            Box::new(AttributeMarkerAvailability::new("unwrap-indent")),
+            Box::new(UnwrapIndentMarkerBuilder::default()),
+        );
+        let (unwrap_block_availability, unwrap_block_builder) = build_strategy(
+            strategy_config,
+            RemovalStrategy::UnwrapBlock,
+            Box::new(AttributeMarkerAvailability::new("unwrap-block")),
+            build_unwrap_block_marker_builder(strategy_config),
+        );
+        let (range_availability, range_builder) = build_strategy(
+            strategy_config,
+            RemovalStrategy::Range,
+            Box::new(RangeMarkerAvailability::default()),
+            Box::new(RangeMarkerBuilder::default()),
+        );
+        let time_released_evaluator = TimeReleasedEvaluator {
+            current_time: config.time_limited_configuration.current,
+            time_offset: config.time_limited_configuration.time_offset.clone(),
+            remove_before_release: config.time_released_configuration.before_release
+                == BeforeRelease::Remove,
+        };
+
+        Self::new(delimiters)
+            .removal_evaluator(
+                config.time_limited_configuration.tag_name,
+                Box::new(TimeLimitedEvaluator {
+                    current_time: config.time_limited_configuration.current,
+                    time_offset: config.time_limited_configuration.time_offset,
+                }),
+            )
+            .removal_evaluator(
+                config.time_released_configuration.tag_name.clone(),
+                Box::new(time_released_evaluator.clone()),
+            )
+            .removal_evaluator(
+                config.removal_marker_configuration.tag_name,
+                Box::new(MarkerEvaluator {
+                    marker_removal_names: config.removal_marker_configuration.targets,
+                }),
+            )
+            .remove_strategy(
+                STRIP_TAGS_STRATEGY_PRIORITY,
+                // The strategy configured for the tag takes precedence.
+                Box::new(TagNameMarkerAvailability::new(
+                    Box::new(TimeReleasedMarkerAvailability::new(
+                        config.time_released_configuration.tag_name,
+                        time_released_evaluator,
+                    )),
+                    HashSet::new(),
+                    strategy_config.tag_strategies.keys().cloned().collect(),
+                )),
+                // Released content is revealed even if `strip-tags` is disabled.
+                Box::new(StripTagsMarkerBuilder::default()),
+            )
+            .remove_strategy(
+                STRIP_TAGS_STRATEGY_PRIORITY,
+                strip_tags_availability,
+                strip_tags_builder,
+            )
+            .remove_strategy(
+                UNWRAP_ELSE_STRATEGY_PRIORITY,
+                unwrap_else_availability,
+                unwrap_else_builder,
+            )
+            .remove_strategy(
+                UNWRAP_INDENT_STRATEGY_PRIORITY,
+                unwrap_indent_availability,
+                unwrap_indent_builder,
+            )
+            .remove_strategy(
+                UNWRAP_BLOCK_STRATEGY_PRIORITY,
+                unwrap_block_availability,
+                unwrap_block_builder,
+            )
+            .remove_strategy(RANGE_STRATEGY_PRIORITY, range_availability, range_builder)
+    }
+
+    /// Register the evaluator deciding whether elements with the tag name are removed.
+    /// An evaluator already registered under the same tag name is replaced.
+    pub fn removal_evaluator(
+        mut self,
+        tag_name: impl Into<String>,
+        evaluator: Box<dyn RemovalEvaluator>,
+    ) -> Self {
+        self.removal_evaluators.insert(tag_name.into(), evaluator);
+        self
+    }
+
+    /// Register the strategy deciding the range to be removed.
+    ///
+    /// The first available strategy in descending order of priority is used.
+    /// Strategies with the same priority are tried in order of registration.
+    pub fn remove_strategy(
+        mut self,
+        priority: i32,
+        availability: Box<dyn MarkerAvailability>,
+        builder: Box<dyn MarkerBuilder>,
+    ) -> Self {
+        self.remove_strategies
+            .push((priority, availability, builder));
+        self
+    }
+
+    pub fn build(mut self) -> Chiritori {
+        // A stable sort keeps the order of registration within the same priority.
+        self.remove_strategies
+            .sort_by_key(|(priority, _, _)| std::cmp::Reverse(*priority));
+        let mut tag_names: Vec<String> = self.removal_evaluators.keys().cloned().collect();
+        tag_names.sort();
+        let remove_strategies: RemoveStrategies = self
+            .remove_strategies
+            .into_iter()
+            .map(|(_, availability, builder)| (availability, builder))
+            .collect();
+
+        Chiritori {
+            delimiters: self.delimiters,
+            tag_names,
+            remover: Remover::new(self.removal_evaluators, remove_strategies),
+        }
+    }
+}
+
+/// Removes, lists and checks elements with the registered evaluators and strategies.
+/// It can be reused for any number of source files.
+pub struct Chiritori {
+    delimiters: Vec<(String, String)>,
+    tag_names: Vec<String>,
+    remover: Remover,
+}
+
+impl Chiritori {
+    /// Remove elements ready to be removed.
+    pub fn clean(&self, content: &str) -> String {
+        self.clean_with_report(content).0
+    }
+
+    /// Remove elements ready to be removed, and find elements that cannot be evaluated in the same pass.
+    pub fn clean_with_report(&self, content: &str) -> (String, Vec<InvalidElement>) {
+        let tokens = self.tokenize(content);
+        let parsed = parser::parse(&tokens);
+        let invalid_elements = self.remover.find_invalid_elements(&parsed);
+        let (removed, markers) = self.remover.remove(parsed, content);
+
+        let removed_pos = remover::get_removed_pos(&markers);
+        let formatter = build_formatters();
+        let structure_formatters: Vec<Box<dyn BlockFormatter>> = vec![Box::new(
+            formatter::block_indent_remover::BlockIndentRemover {},
+        )];
+
+        (
+            formatter::format(&removed, &removed_pos, &formatter, &structure_formatters),
+            invalid_elements,
+        )
+    }
+
+    /// Build a unified diff of the changes made by `clean`.
+    pub fn diff(&self, content: &str, path: &str) -> String {
+        build_unified_diff(content, &self.clean(content), path)
+    }
+
+    pub fn list(&self, content: &str, format: ListFormat) -> Result<String, ListError> {
+        Ok(self.list_with_report(content, format)?.0)
+    }
+
+    /// List elements ready to be removed together with the elements that cannot be evaluated.
+    pub fn list_with_report(
+        &self,
+        content: &str,
+        format: ListFormat,
+    ) -> Result<(String, Vec<InvalidElement>), ListError> {
+        let (markers, invalid_elements) = self.build_ready_markers(content);
+        let list = format_list(content, &markers, &invalid_elements, format)?;
+
+        Ok((list, invalid_elements))
+    }
+
+    pub fn list_all(&self, content: &str, format: ListFormat) -> Result<String, ListError> {
+        Ok(self.list_all_with_report(content, format)?.0)
+    }
+
+    /// List all elements including pending ones together with the elements that cannot be evaluated.
+    pub fn list_all_with_report(
+        &self,
+        content: &str,
+        format: ListFormat,
+    ) -> Result<(String, Vec<InvalidElement>), ListError> {
+        let tokens = self.tokenize(content);
+        let parsed = parser::parse(&tokens);
+        let markers = self
+            .remover
+            .build_remove_marker_all_with_origin(&parsed, content);
+        let invalid_elements = self.remover.find_invalid_elements(&parsed);
+        let list = format_list(content, &markers, &invalid_elements, format)?;
+
+        Ok((list, invalid_elements))
+    }
+
+    /// Find elements whose removal cannot be evaluated because of missing or malformed attributes.
+    pub fn find_invalid_elements(&self, content: &str) -> Vec<InvalidElement> {
+        let tokens = self.tokenize(content);
+        let parsed = parser::parse(&tokens);
+
+        self.remover.find_invalid_elements(&parsed)
+    }
+
+    /// Check whether the source code contains elements ready to be removed.
+    pub fn check(&self, content: &str, format: ListFormat) -> Result<CheckResult, ListError> {
+        let (markers, invalid_elements) = self.build_ready_markers(content);
+
+        // Paired markers (e.g. unwrap-block) are counted as one element.
+        let removal_count = markers
+            .iter()
+            .enumerate()
+            .filter(|(idx, ((_, pair), _, _))| pair.is_none_or(|pair| pair > *idx))
+            .count();
+
+        Ok(CheckResult {
+            removal_count,
+            report: format_list(content, &markers, &invalid_elements, format)?,
+            invalid_elements,
+        })
+    }
+
+    /// Validate the structure of tags and report problems with their line and column.
+    /// The tag names of the registered evaluators are used to detect typos.
+    pub fn validate(&self, content: &str) -> Vec<ValidationError> {
+        let tokens = self.tokenize(content);
+        let known_tag_names: Vec<&str> = self.tag_names.iter().map(|v| v.as_str()).collect();
+
+        let mut errors = validator::validate(content, &tokens, &known_tag_names);
+
+        let line_map = build_line_map(content);
+        let delimiters = self.delimiter_pairs();
+        errors.extend(
+            tokenizer::find_unterminated_delimiters(&tokens, &delimiters)
+                .into_iter()
+                .map(|(range, start, end)| {
+                    ValidationError::new(
+                        content,
+                        &line_map,
+                        ValidationErrorKind::UnterminatedDelimiter {
+                            start: start.to_string(),
+                            end: end.to_string(),
+                        },
+                        range,
+                    )
+                }),
+        );
+
+        let parsed = parser::parse(&tokens);
+        errors.extend(
+            self.remover
+                .find_invalid_elements(&parsed)
+                .into_iter()
+                .map(|el| {
+                    ValidationError::new(
+                        content,
+                        &line_map,
+                        ValidationErrorKind::InvalidElement {
+                            tag_name: el.tag_name,
+                            error: el.error,
+                        },
+                        el.byte_range,
+                    )
+                }),
+        );
+        errors.extend(
+            self.remover
+                .find_ineffective_elements(&parsed, content)
+                .into_iter()
+                .map(|el| {
+                    ValidationError::new(
+                        content,
+                        &line_map,
+                        ValidationErrorKind::IneffectiveStrategy {
+                            tag_name: el.tag_name,
+                            strategy: el.strategy,
+                        },
+                        el.byte_range,
+                    )
+                }),
+        );
+        errors.sort_by_key(|e| e.byte_range.start);
+
+        errors
+    }
+
+    fn tokenize<'a>(&'a self, content: &'a str) -> Vec<tokenizer::Token<'a, 'a, 'a>> {
+        tokenizer::tokenize_with_delimiters(content, &self.delimiter_pairs())
+    }
+
+    fn delimiter_pairs(&self) -> Vec<(&str, &str)> {
+        self.delimiters
+            .iter()
+            .map(|(start, end)| (start.as_str(), end.as_str()))
+            .collect()
+    }
+
+    fn build_ready_markers(
+        &self,
+        content: &str,
+    ) -> (Vec<(RemoveMarker, bool, MarkerOrigin)>, Vec<InvalidElement>) {
+        let tokens = self.tokenize(content);
+        let parsed = parser::parse(&tokens);
+        let markers = self
+            .remover
+            .build_remove_marker_with_origin(&parsed, content)
+            .into_iter()
+            .map(|(marker, origin)| (marker, true, origin))
+            .collect();
+
+        (markers, self.remover.find_invalid_elements(&parsed))
+    }
+}
+
+fn format_list(
+    content: &str,
+    markers: &[(RemoveMarker, bool, MarkerOrigin)],
+    invalid_elements: &[InvalidElement],
+    format: ListFormat,
+) -> Result<String, ListError> {
+    let line_map = build_line_map(content);
+
+    match format {
+        ListFormat::PrettyString => Ok(build_pretty_string(
+            content,
+            markers,
+            invalid_elements,
+            Some(&line_map),
+        )),
+        ListFormat::JSON => serde_json::to_string(&build_list(
+            content,
+            markers,
+            invalid_elements,
+            Some(&line_map),
+        ))
+        .map_err(|_| ListError::JSONSerializeError),
+    }
+}
+
+fn build_strategy(
+    config: &RemovalStrategyConfiguration,
+    strategy: RemovalStrategy,
+    availability: Box<dyn MarkerAvailability>,
+    builder: Box<dyn MarkerBuilder>,
+) -> (Box<dyn MarkerAvailability>, Box<dyn MarkerBuilder>) {
+    let (tag_names, other_tag_names): (HashSet<_>, HashSet<_>) = config
+        .tag_strategies
+        .iter()
+        .map(|(tag_name, tag_strategy)| (tag_name.clone(), *tag_strategy))
+        .partition(|(_, tag_strategy)| *tag_strategy == strategy);
+    let availability = Box::new(TagNameMarkerAvailability::new(
+        availability,
+        tag_names
+            .into_iter()
+            .map(|(tag_name, _)| tag_name)
+            .collect(),
+        other_tag_names
+            .into_iter()
+            .map(|(tag_name, _)| tag_name)
+            .collect(),
+    ));
+
+    // Elements requiring a disabled strategy are not removed.
+    if config.enabled_strategies.contains(&strategy) {
+        (availability, builder)
+    } else {
+        (availability, Box::new(SkipMarkerBuilder::default()))
+    }
+}
+
+/// Build the builder of `unwrap-block` using the syntax tree of the language if it is supported.
+#[cfg(feature = "tree-sitter")]
+fn build_unwrap_block_marker_builder(
+    config: &RemovalStrategyConfiguration,
+) -> Box<dyn MarkerBuilder> {
+    match config
+        .language
+        .as_deref()
+        .and_then(SyntaxLanguage::from_name)
+    {
+        Some(language) => Box::new(SyntaxUnwrapBlockMarkerBuilder::new(language)),
+        None => Box::new(UnwrapBlockMarkerBuilder::default()),
+    }
+}
+
+#[cfg(not(feature = "tree-sitter"))]
+fn build_unwrap_block_marker_builder(
+    _config: &RemovalStrategyConfiguration,
+) -> Box<dyn MarkerBuilder> {
+    Box::new(UnwrapBlockMarkerBuilder::default())
+}
+
+fn build_formatters() -> Vec<Box<dyn Formatter>> {
+    vec![
+        Box::new(formatter::indent_remover::IndentRemover {}),
+        Box::new(formatter::empty_line_remover::EmptyLineRemover {}),
+        Box::new(formatter::prev_line_break_remover::PrevLineBreakRemover {}),
+        Box::new(formatter::next_line_break_remover::NextLineBreakRemover {}),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code::remover::removal_evaluator::EvaluationError;
+    use crate::element_parser::Element;
+
+    /// Removes elements whose `env` attribute is "dev".
+    struct EnvEvaluator {}
+
+    impl RemovalEvaluator for EnvEvaluator {
+        fn is_removal(&self, start_el: &Element) -> Result<bool, EvaluationError> {
+            start_el
+                .attrs
+                .iter()
+                .find(|a| a.name == "env")
+                .and_then(|a| a.value.as_deref())
+                .map(|env| env == "dev")
+                .ok_or_else(|| EvaluationError::MissingAttribute("env".to_string()))
+        }
+    }
+
+    /// Removes only the tags and keeps the content.
+    struct TagOnlyAvailability {}
+
+    impl MarkerAvailability for TagOnlyAvailability {
+        fn is_available(&self, element: &parser::Element) -> bool {
+            element
+                .start_element
+                .attrs
+                .iter()
+                .any(|a| a.name == "tag-only")
+        }
+    }
+
+    struct TagOnlyMarkerBuilder {}
+
+    impl MarkerBuilder for TagOnlyMarkerBuilder {
+        fn build(
+            &self,
+            _content: &str,
+            el: &parser::Element,
+        ) -> (std::ops::Range<usize>, Option<std::ops::Range<usize>>) {
+            (
+                el.start_token.byte_start..el.start_token.byte_end,
+                Some(el.end_token.byte_start..el.end_token.byte_end),
+            )
+        }
+
+        fn strategy_name(&self) -> &'static str {
+            "tag-only"
+        }
+    }
+
+    fn create_builder() -> ChiritoriBuilder {
+        ChiritoriBuilder::new(vec![(String::from("<"), String::from(">"))])
+            .removal_evaluator("env", Box::new(EnvEvaluator {}))
+            .remove_strategy(
+                RANGE_STRATEGY_PRIORITY,
+                Box::new(RangeMarkerAvailability::default()),
+                Box::new(RangeMarkerBuilder::default()),
+            )
+    }
+
+    #[test]
+    fn test_custom_evaluator() {
+        let chiritori = create_builder().build();
+
+        assert_eq!(
+            chiritori.clean("a<env env='dev'>b</env>c<env env='prod'>d</env>e"),
+            "ac<env env='prod'>d</env>e"
+        );
+        assert_eq!(
+            chiritori.clean("a<env env='prod'>b</env>c<env env='dev'>d</env>e"),
+            "a<env env='prod'>b</env>ce"
+        );
+        assert_eq!(chiritori.find_invalid_elements("<env>a</env>").len(), 1);
+    }
+
+    #[test]
+    fn test_strategy_priority() {
+        let content = "a<env env='dev' tag-only>b</env>c";
+
+        // The range strategy is always available, so a strategy with a lower priority is not used.
+        let chiritori = create_builder()
+            .remove_strategy(
+                RANGE_STRATEGY_PRIORITY - 1,
+                Box::new(TagOnlyAvailability {}),
+                Box::new(TagOnlyMarkerBuilder {}),
+            )
+            .build();
+        assert_eq!(chiritori.clean(content), "ac");
+
+        let chiritori = create_builder()
+            .remove_strategy(
+                RANGE_STRATEGY_PRIORITY + 1,
+                Box::new(TagOnlyAvailability {}),
+                Box::new(TagOnlyMarkerBuilder {}),
+            )
+            .build();
+        assert_eq!(chiritori.clean(content), "abc");
+        assert!(chiritori
+            .list(content, ListFormat::JSON)
+            .unwrap()
+            .contains(r#""strategy":"tag-only""#));
+    }
+
+    #[test]
+    fn test_validate_with_registered_tag_names() {
+        let chiritori = create_builder().build();
+
+        let errors = chiritori.validate("<enf env='dev'>a</env>");
+        assert_eq!(
+            errors[0].kind,
+            ValidationErrorKind::UnknownTagName {
+                name: "enf".to_string(),
+                suggestion: "env".to_string()
+            }
+        );
+    }
+}
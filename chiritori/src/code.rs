@@ -1,4 +1,7 @@
+pub mod diff;
 pub mod formatter;
 pub mod list;
 pub mod remover;
+#[cfg(feature = "tree-sitter")]
+pub mod syntax;
 pub mod utils;
@@ -0,0 +1,70 @@
+use similar::TextDiff;
+
+const CONTEXT_RADIUS: usize = 3;
+
+/// Build a unified diff between the original content and the modified content.
+///
+/// The diff has the file headers of `git diff`, so that it can be applied with `git apply`.
+/// An empty string is returned if there is no difference.
+///
+/// ```text
+/// diff --git a/src/index.js b/src/index.js
+/// --- a/src/index.js
+/// +++ b/src/index.js
+/// @@ -1,3 +1 @@
+///  foo
+/// -/* <time-limited to="2001-01-01 00:00:00"> */
+/// -bar
+/// -/* </time-limited> */
+/// ```
+pub fn build_unified_diff(original: &str, modified: &str, path: &str) -> String {
+    if original == modified {
+        return String::new();
+    }
+
+    let path = path.replace('\\', "/");
+    let path = path.trim_start_matches("./");
+    let diff = TextDiff::from_lines(original, modified);
+    let old_header = format!("a/{}", path);
+    let new_header = format!("b/{}", path);
+
+    let mut output = format!("diff --git {} {}\n", old_header, new_header);
+    output.push_str(
+        &diff
+            .unified_diff()
+            .context_radius(CONTEXT_RADIUS)
+            .header(&old_header, &new_header)
+            .to_string(),
+    );
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(
+        "foo\n<tl>\nbar\n</tl>\nbaz\n",
+        "foo\nbaz\n",
+        "./src/a.js",
+        "diff --git a/src/a.js b/src/a.js\n--- a/src/a.js\n+++ b/src/a.js\n@@ -1,5 +1,2 @@\n foo\n-<tl>\n-bar\n-</tl>\n baz\n"
+    )]
+    #[case(
+        "foo\n<tl>bar</tl>",
+        "foo\n",
+        "a.js",
+        "diff --git a/a.js b/a.js\n--- a/a.js\n+++ b/a.js\n@@ -1,2 +1 @@\n foo\n-<tl>bar</tl>\n\\ No newline at end of file\n"
+    )]
+    #[case("foo\n", "foo\n", "a.js", "")]
+    fn test_build_unified_diff(
+        #[case] original: &str,
+        #[case] modified: &str,
+        #[case] path: &str,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(build_unified_diff(original, modified, path), expected);
+    }
+}
@@ -294,6 +294,6 @@ mod tests {
 
         let mut ranges = vec![];
         merge_overlapped_ranges(&mut ranges);
-        assert_eq!(ranges, vec![]);
+        assert!(ranges.is_empty());
     }
 }
@@ -1,7 +1,9 @@
 use super::BlockFormatter;
 use crate::code::utils::{
     char_pos_finder::find_next_char_pos,
-    line_break_pos_finder::{find_next_line_break_pos, find_prev_line_break_pos},
+    line_break_pos_finder::{
+        find_next_line_break_pos, find_prev_line_break_pos, line_break_len_at,
+    },
 };
 use std::ops::Range;
 
@@ -48,7 +50,8 @@ impl BlockFormatter for BlockIndentRemover {
             Some(pos) => start_byte_pos - pos - 1,
             None => 0,
         };
-        let mut current_pos = start_byte_pos + 1;
+        let mut current_pos =
+            start_byte_pos + line_break_len_at(bytes, start_byte_pos).unwrap_or(1);
         let first_indent_len = get_indent_len(content, current_pos);
         let indent_len = first_indent_len.saturating_sub(indent_ofs);
 
@@ -85,7 +88,9 @@ impl BlockFormatter for BlockIndentRemover {
 fn get_indent_len(content: &str, byte_pos: usize) -> usize {
     let bytes = content.as_bytes();
 
+    // A line break at the beginning of the content is not found by `find_prev_line_break_pos`.
     find_prev_line_break_pos(content, bytes, byte_pos, false)
+        .or_else(|| (byte_pos > 0 && bytes.first() == Some(&b'\n')).then_some(0))
         .and_then(|p| find_next_char_pos(content, bytes, p + 1).map(|e| e - p - 1))
         .unwrap_or(0)
 }
@@ -174,7 +179,7 @@ mod tests {
         //             012345678901234567890123456
         //             |      ^               ^
         let content = "foo+   +  fuga++  piyo++bar".replace('+', "\n");
-        assert_eq!(remover.format(&content, 7, 20), vec![]);
+        assert!(remover.format(&content, 7, 20).is_empty());
 
         //    original          removed          formatted
         // +------------+    +------------+    +------------+
@@ -201,4 +206,28 @@ mod tests {
             vec![14..16, 31..32, 36..38, 42..44]
         );
     }
+
+    #[test]
+    fn test_format_with_crlf() {
+        let remover = BlockIndentRemover {};
+
+        // '+' is CRLF
+        //                      10        20
+        //             012345678901234567890123456
+        //             |    ^ <>      <>      ^
+        let content = "foo++  fuga+  piyo++bar".replace('+', "\r\n");
+        assert_eq!(remover.format(&content, 5, 23), vec![7..9, 15..17]);
+    }
+
+    #[test]
+    fn test_format_at_beginning() {
+        let remover = BlockIndentRemover {};
+
+        // The block is at the beginning of the content.
+        //                      10
+        //             012345678901234567
+        //             ^<>     <>     ^
+        let content = "+  fuga+  piyo++bar".replace('+', "\n");
+        assert_eq!(remover.format(&content, 0, 15), vec![1..3, 8..10]);
+    }
 }
@@ -1,5 +1,5 @@
 use crate::code::utils::line_break_pos_finder::{
-    find_next_line_break_pos, find_prev_line_break_pos,
+    find_next_line_break_pos, find_prev_line_break_pos, line_break_len_at,
 };
 
 use super::Formatter;
@@ -51,7 +51,17 @@ impl Formatter for EmptyLineRemover {
             panic!("Invalid byte position: {}", byte_pos);
         }
 
-        if bytes.get(byte_pos) != Some(&b'\n') {
+        let Some(line_break_len) = line_break_len_at(bytes, byte_pos) else {
+            return (byte_pos, byte_pos);
+        };
+
+        // The line still has code before the deletion position (e.g. a trailing comment is deleted).
+        let is_blank_line = bytes[..byte_pos]
+            .iter()
+            .rev()
+            .take_while(|b| **b != b'\n')
+            .all(|b| matches!(b, b' ' | b'\t'));
+        if !is_blank_line {
             return (byte_pos, byte_pos);
         }
 
@@ -63,7 +73,7 @@ impl Formatter for EmptyLineRemover {
             .is_none();
 
         if is_not_next_line_empty && is_not_prev_line_empty {
-            (byte_pos, byte_pos + 1)
+            (byte_pos, byte_pos + line_break_len)
         } else {
             (byte_pos, byte_pos)
         }
@@ -101,5 +111,26 @@ mod tests {
         //             |         ^
         let content = "    hoge++ +  foo".replace('+', "\n");
         assert_eq!(remover.format(&content, 10), (10, 10));
+
+        //                      10
+        //             0123456789012345
+        //             |      ^
+        let content = "  a(); +  c();".replace('+', "\n");
+        assert_eq!(remover.format(&content, 7), (7, 7));
+    }
+
+    #[test]
+    fn test_format_with_crlf() {
+        let remover = EmptyLineRemover {};
+
+        // '+' is CRLF
+        //                      10
+        //             0123456789012345
+        //             |         ^
+        let content = "    hoge++  foo".replace('+', "\r\n");
+        assert_eq!(remover.format(&content, 10), (10, 12));
+
+        let content = "    hoge+++  foo".replace('+', "\r\n");
+        assert_eq!(remover.format(&content, 10), (10, 10));
     }
 }
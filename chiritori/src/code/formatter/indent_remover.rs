@@ -1,4 +1,6 @@
 use super::Formatter;
+use crate::code::utils::line_break_pos_finder::line_break_len_at;
+
 pub struct IndentRemover {}
 
 impl Formatter for IndentRemover {
@@ -28,7 +30,7 @@ impl Formatter for IndentRemover {
         let mut cursor = byte_pos;
         let bytes = content.as_bytes();
 
-        if cursor >= bytes.len() || !content.is_char_boundary(cursor) || bytes[cursor] != b'\n' {
+        if !content.is_char_boundary(cursor) || line_break_len_at(bytes, cursor).is_none() {
             return (byte_pos, byte_pos);
         }
 
@@ -117,4 +119,16 @@ mod tests {
         let content = "\n".to_string();
         assert_eq!(remover.format(&content, 0), (0, 0));
     }
+
+    #[test]
+    fn test_format_with_crlf() {
+        let remover = IndentRemover {};
+
+        // '+' is CRLF
+        //                      10        20
+        //             0123456789012345678901234567
+        //             |                  ^   ^
+        let content = "+<div>+    hoge+    +    foo</div>".replace('+', "\r\n");
+        assert_eq!(remover.format(&content, 23), (19, 23));
+    }
 }
@@ -1,4 +1,4 @@
-use crate::code::utils::line_break_pos_finder::find_next_line_break_pos;
+use crate::code::utils::line_break_pos_finder::{find_next_line_break_pos, line_break_start};
 
 use super::Formatter;
 pub struct NextLineBreakRemover {}
@@ -36,7 +36,7 @@ impl Formatter for NextLineBreakRemover {
             .and_then(|pos| find_next_line_break_pos(content, bytes, pos + 1, true));
 
         if let Some(line_break_pos) = line_break_pos {
-            (byte_pos, line_break_pos)
+            (byte_pos, line_break_start(bytes, line_break_pos))
         } else {
             (byte_pos, byte_pos)
         }
@@ -99,4 +99,17 @@ mod tests {
         let content = "\n".to_string();
         assert_eq!(remover.format(&content, 0), (0, 0));
     }
+
+    #[test]
+    fn test_format_with_crlf() {
+        let remover = NextLineBreakRemover {};
+
+        // '+' is CRLF
+        //                      10        20
+        //             0123456789012345678901234567
+        //             |             ^   ^
+        let content = "    hoge+    +  +    foo</div>".replace('+', "\r\n");
+        // The line break at the end is kept as CRLF.
+        assert_eq!(remover.format(&content, 14), (14, 18));
+    }
 }
@@ -1,4 +1,4 @@
-use crate::code::utils::line_break_pos_finder::find_prev_line_break_pos;
+use crate::code::utils::line_break_pos_finder::{find_prev_line_break_pos, line_break_len_at};
 
 use super::Formatter;
 
@@ -33,6 +33,19 @@ impl Formatter for PrevLineBreakRemover {
     fn format(&self, content: &str, byte_pos: usize) -> (usize, usize) {
         let bytes = content.as_bytes();
 
+        // The indent of the line having code after the removal position is kept
+        // (e.g. only a leading comment is removed).
+        let rest_pos = byte_pos
+            + bytes[byte_pos..]
+                .iter()
+                .take_while(|b| matches!(b, b' ' | b'\t'))
+                .count();
+        let has_code_after = rest_pos < bytes.len() && line_break_len_at(bytes, rest_pos).is_none();
+        let has_indent_before = byte_pos > 0 && matches!(bytes[byte_pos - 1], b' ' | b'\t');
+        if has_code_after && has_indent_before {
+            return (byte_pos, byte_pos);
+        }
+
         let line_break_pos = find_prev_line_break_pos(content, bytes, byte_pos, true)
             .and_then(|pos| find_prev_line_break_pos(content, bytes, pos, true));
 
@@ -109,5 +122,23 @@ mod tests {
 
         let content = "\n".to_string();
         assert_eq!(remover.format(&content, 0), (0, 0));
+
+        //                      10
+        //             01234567890123
+        //             |     ^
+        let content = "a+  +   b();".replace('+', "\n");
+        assert_eq!(remover.format(&content, 6), (6, 6));
+    }
+
+    #[test]
+    fn test_format_with_crlf() {
+        let remover = PrevLineBreakRemover {};
+
+        // '+' is CRLF
+        //                      10        20
+        //             0123456789012345678901234567
+        //             |         ^     ^
+        let content = "    hoge++    +    foo</div>".replace('+', "\r\n");
+        assert_eq!(remover.format(&content, 16), (10, 16));
     }
 }
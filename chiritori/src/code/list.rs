@@ -1,12 +1,13 @@
 use crate::code::utils::blank_counter;
 
 use super::{
-    remover::RemoveMarker,
+    remover::{InvalidElement, MarkerOrigin, ParentElement, RemoveMarker},
     utils::{
         line_break_pos_finder::{find_next_line_break_pos, find_prev_line_break_pos},
-        line_map::find_line,
+        line_map::{find_line, find_line_column},
     },
 };
+use chrono::{DateTime, FixedOffset};
 use serde::Serialize;
 use std::ops::Range;
 
@@ -22,6 +23,8 @@ const HEAD_START: &str = "-------- [ ";
 const HEAD_END: &str = "--------";
 const REMOVAL_HEAD: &str = " ]  Ready  ";
 const PENDING_REMOVAL_HEAD: &str = " ] Pending ";
+const INVALID_HEAD: &str = " ] Invalid ";
+const INVALID_MESSAGE_HEAD: &str = "  error: ";
 const LINE_COLUMN_WIDTH: usize = 9;
 
 const TABSPACE: &str = "    ";
@@ -87,7 +90,8 @@ pub fn build_pretty_string_item(
             .collect::<Vec<_>>()
             .join("\n"),
     );
-    removed.push_str(&content[color_end..line_end]);
+    // "\r" of CRLF is not a part of the code block.
+    removed.push_str(content[color_end..line_end].trim_end_matches('\r'));
     removed.push('\n');
 
     let (code_block, line_number_ofs) = if let Some(line_range) = line_range {
@@ -144,30 +148,43 @@ pub fn build_pretty_string_item(
 
 pub fn build_pretty_string(
     content: &str,
-    markers: &[(RemoveMarker, bool)],
+    markers: &[(RemoveMarker, bool, MarkerOrigin)],
+    invalid_elements: &[InvalidElement],
     line_map: Option<&Vec<usize>>,
 ) -> String {
-    let mut output: String = markers
+    let entries = build_entries(markers, invalid_elements);
+    let mut output: String = entries
         .iter()
-        .zip(1..=markers.len())
-        .map(|(((range, _), is_removal), idx)| {
+        .zip(1..=entries.len())
+        .map(|(entry, idx)| {
+            let Entry {
+                range,
+                status,
+                message,
+                ..
+            } = entry;
             let line_range = line_map.map(|m| get_line_range(m, range));
 
             let mut res = String::from("\n");
             res.push_str(HEAD_START);
             res.push_str(&idx.to_string());
-            res.push_str(if *is_removal {
-                REMOVAL_HEAD
-            } else {
-                PENDING_REMOVAL_HEAD
+            res.push_str(match status {
+                ItemStatus::Ready => REMOVAL_HEAD,
+                ItemStatus::Pending => PENDING_REMOVAL_HEAD,
+                ItemStatus::Invalid => INVALID_HEAD,
             });
             res.push_str(HEAD_END);
             res.push('\n');
+            if let Some(message) = message {
+                res.push_str(INVALID_MESSAGE_HEAD);
+                res.push_str(message);
+                res.push('\n');
+            }
             res.push_str(&build_pretty_string_item(
                 content,
                 range.start,
                 range.end,
-                *is_removal,
+                *status == ItemStatus::Ready,
                 true,
                 line_range,
             ));
@@ -181,10 +198,11 @@ pub fn build_pretty_string(
     output
 }
 
-#[derive(Debug, PartialEq, Serialize)]
+#[derive(Debug, PartialEq, Clone, Copy, Serialize)]
 pub enum ItemStatus {
     Ready,
     Pending,
+    Invalid,
 }
 
 #[derive(Debug, PartialEq, Serialize)]
@@ -192,38 +210,153 @@ pub struct ListItem {
     line_range: Option<(usize, usize)>,
     annotated_code_block: String,
     current_status: ItemStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+    tag_name: String,
+    attributes: Vec<ListItemAttribute>,
+    /// The strategy which removes the item (`None` if the item is invalid)
+    strategy: Option<&'static str>,
+    /// The range to be removed (in bytes, end exclusive)
+    byte_range: (usize, usize),
+    /// The range to be removed (in characters, end exclusive)
+    char_range: (usize, usize),
+    /// The 1-based column of the first character
+    start_column: Option<usize>,
+    /// The 1-based column of the last character
+    end_column: Option<usize>,
+    /// RFC 3339
+    expires_at: Option<String>,
+    parent: Option<ListItemParent>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ListItemAttribute {
+    name: String,
+    value: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ListItemParent {
+    tag_name: String,
+    /// The range of the start tag (in bytes, end exclusive)
+    byte_range: (usize, usize),
+    line: Option<usize>,
 }
 
 pub fn build_list(
     content: &str,
-    markers: &[(RemoveMarker, bool)],
+    markers: &[(RemoveMarker, bool, MarkerOrigin)],
+    invalid_elements: &[InvalidElement],
     line_map: Option<&Vec<usize>>,
 ) -> Vec<ListItem> {
-    markers
-        .iter()
-        .map(|((range, _), is_removal)| {
+    build_entries(markers, invalid_elements)
+        .into_iter()
+        .map(|entry| {
+            let range = entry.range;
             let line_range = line_map.map(|m| get_line_range(m, range));
             let text = build_pretty_string_item(
                 content,
                 range.start,
                 range.end,
-                *is_removal,
+                entry.status == ItemStatus::Ready,
                 false,
                 line_range,
             );
+            let (start_column, end_column) = match line_map {
+                Some(line_map) => {
+                    let last_char_pos = content[..range.end]
+                        .char_indices()
+                        .next_back()
+                        .map_or(range.start, |(pos, _)| pos.max(range.start));
+                    (
+                        Some(find_line_column(content, line_map, range.start).1),
+                        Some(find_line_column(content, line_map, last_char_pos).1),
+                    )
+                }
+                None => (None, None),
+            };
+            let char_start = content[..range.start].chars().count();
 
             ListItem {
                 line_range,
                 annotated_code_block: text,
-                current_status: match is_removal {
-                    true => ItemStatus::Ready,
-                    false => ItemStatus::Pending,
-                },
+                current_status: entry.status,
+                message: entry.message,
+                tag_name: entry.tag_name.to_string(),
+                attributes: entry
+                    .attributes
+                    .iter()
+                    .map(|(name, value)| ListItemAttribute {
+                        name: name.clone(),
+                        value: value.clone(),
+                    })
+                    .collect(),
+                strategy: entry.strategy,
+                byte_range: (range.start, range.end),
+                char_range: (
+                    char_start,
+                    char_start + content[range.clone()].chars().count(),
+                ),
+                start_column,
+                end_column,
+                expires_at: entry.expires_at.map(|v| v.to_rfc3339()),
+                parent: entry.parent.map(|parent| ListItemParent {
+                    tag_name: parent.tag_name.clone(),
+                    byte_range: (parent.byte_range.start, parent.byte_range.end),
+                    line: line_map.map(|m| find_line(m, parent.byte_range.start)),
+                }),
             }
         })
         .collect()
 }
 
+struct Entry<'a> {
+    range: &'a Range<usize>,
+    status: ItemStatus,
+    message: Option<String>,
+    tag_name: &'a str,
+    attributes: &'a [(String, Option<String>)],
+    strategy: Option<&'static str>,
+    expires_at: Option<&'a DateTime<FixedOffset>>,
+    parent: Option<&'a ParentElement>,
+}
+
+/// Merge markers and invalid elements in order of position.
+fn build_entries<'a>(
+    markers: &'a [(RemoveMarker, bool, MarkerOrigin)],
+    invalid_elements: &'a [InvalidElement],
+) -> Vec<Entry<'a>> {
+    let mut entries: Vec<_> = markers
+        .iter()
+        .map(|((range, _), is_removal, origin)| Entry {
+            range,
+            status: match is_removal {
+                true => ItemStatus::Ready,
+                false => ItemStatus::Pending,
+            },
+            message: None,
+            tag_name: &origin.tag_name,
+            attributes: &origin.attributes,
+            strategy: Some(origin.strategy),
+            expires_at: origin.expires_at.as_ref(),
+            parent: origin.parent.as_ref(),
+        })
+        .chain(invalid_elements.iter().map(|el| Entry {
+            range: &el.byte_range,
+            status: ItemStatus::Invalid,
+            message: Some(el.to_string()),
+            tag_name: &el.tag_name,
+            attributes: &el.attributes,
+            strategy: None,
+            expires_at: None,
+            parent: el.parent.as_ref(),
+        }))
+        .collect();
+    entries.sort_by_key(|entry| entry.range.start);
+
+    entries
+}
+
 fn get_line_range(line_map: &[usize], range: &Range<usize>) -> (usize, usize) {
     // Subtract one extra line number because of a line break at the end.
     //
@@ -251,6 +384,7 @@ fn get_line_range(line_map: &[usize], range: &Range<usize>) -> (usize, usize) {
 
 #[cfg(test)]
 mod tests {
+    use crate::code::remover::removal_evaluator::EvaluationError;
     use crate::code::utils::line_map::build_line_map;
 
     use super::*;
@@ -328,6 +462,20 @@ eeeeeee";
         );
     }
 
+    #[test]
+    fn test_build_item_with_crlf() {
+        // "\r" of CRLF is not output.
+        //
+        //                      10
+        //             012345678901234
+        let content = "aaa+bbbb+ccc".replace('+', "\r\n");
+
+        assert_eq!(
+            build_pretty_string_item(&content, 5, 7, true, false, None),
+            "_start\nbbbb\n ‾end"
+        );
+    }
+
     #[rstest]
     #[case("aaa+bbbb+ccc+dddd", 4..11, format!("{}{}{}{}{}{}{}{}{}{}{}{}{}", MARKER_START_WITH_COLOR, "+", START_COLOR_YELLOW, "bbbb", RESET_COLOR, "+", START_COLOR_YELLOW, "cc", RESET_COLOR, "c", "+", " ", MARKER_END_WITH_COLOR))]
     fn test_build_item_pending_removal_range(
@@ -343,44 +491,113 @@ eeeeeee";
         );
     }
 
+    fn create_origin(parent: Option<ParentElement>) -> MarkerOrigin {
+        MarkerOrigin {
+            tag_name: "tl".to_string(),
+            attributes: vec![("to".to_string(), Some("2000-01-01".to_string()))],
+            strategy: "range",
+            expires_at: Some(DateTime::parse_from_rfc3339("2000-01-01T00:00:00+09:00").unwrap()),
+            parent,
+        }
+    }
+
     #[test]
     fn test_build_list() {
         //             0123456789012345678
         let content = "aaaa+bbbb+cccc+dddd".replace('+', "\n");
-        let markers = [((1..2, None), true), ((7..14, None), false)];
+        let markers = [
+            ((1..2, None), true, create_origin(None)),
+            ((7..14, None), false, create_origin(None)),
+        ];
         let line_map = build_line_map(&content);
+        let attributes = || {
+            vec![ListItemAttribute {
+                name: "to".to_string(),
+                value: Some("2000-01-01".to_string()),
+            }]
+        };
 
         assert_eq!(
-            build_list(&content, &markers, Some(&line_map)),
+            build_list(&content, &markers, &[], Some(&line_map)),
             vec![
                 ListItem {
                     line_range: Some((1, 1)),
                     annotated_code_block: "          _start\n      1 |aaaa\n          ‾end"
                         .to_string(),
-                    current_status: ItemStatus::Ready
+                    current_status: ItemStatus::Ready,
+                    message: None,
+                    tag_name: "tl".to_string(),
+                    attributes: attributes(),
+                    strategy: Some("range"),
+                    byte_range: (1, 2),
+                    char_range: (1, 2),
+                    start_column: Some(2),
+                    end_column: Some(2),
+                    expires_at: Some("2000-01-01T00:00:00+09:00".to_string()),
+                    parent: None,
                 },
                 ListItem {
                     line_range: Some((2, 3)),
                     annotated_code_block:
                         "           _start\n      2 |bbbb\n      3 |cccc\n            ‾end"
                             .to_string(),
-                    current_status: ItemStatus::Pending
+                    current_status: ItemStatus::Pending,
+                    message: None,
+                    tag_name: "tl".to_string(),
+                    attributes: attributes(),
+                    strategy: Some("range"),
+                    byte_range: (7, 14),
+                    char_range: (7, 14),
+                    start_column: Some(3),
+                    end_column: Some(4),
+                    expires_at: Some("2000-01-01T00:00:00+09:00".to_string()),
+                    parent: None,
                 },
             ]
         )
     }
 
+    #[test]
+    fn test_build_list_with_multibyte_characters() {
+        // byte_pos:   0  3  6 7  10 13 14 17
+        //             あ い +  う え +  お か
+        let content = "あい+うえ+おか".replace('+', "\n");
+        let parent = ParentElement {
+            tag_name: "marker".to_string(),
+            byte_range: 0..3,
+        };
+        let markers = [((10..17, None), true, create_origin(Some(parent)))];
+        let line_map = build_line_map(&content);
+
+        let item = build_list(&content, &markers, &[], Some(&line_map))
+            .pop()
+            .unwrap();
+        assert_eq!(item.byte_range, (10, 17));
+        assert_eq!(item.char_range, (4, 7));
+        assert_eq!((item.start_column, item.end_column), (Some(2), Some(1)));
+        assert_eq!(
+            item.parent,
+            Some(ListItemParent {
+                tag_name: "marker".to_string(),
+                byte_range: (0, 3),
+                line: Some(1),
+            })
+        );
+    }
     #[test]
     fn test_build_pretty_string() {
         //             0123456789012345678
         let content = "aaaa+bbbb+cccc+dddd".replace('+', "\n");
-        let markers = [((1..2, None), true), ((7..12, None), false)];
+        let markers = [
+            ((1..2, None), true, create_origin(None)),
+            ((7..12, None), false, create_origin(None)),
+        ];
 
         let expected_item1 = build_pretty_string_item(&content, 1, 2, true, true, None);
         let expected_item2 = build_pretty_string_item(&content, 7, 12, false, true, None);
 
         assert_eq!(
-            build_pretty_string(&content, &markers, None),
+            build_pretty_string(&content, &markers, &[], None),
             format!(
                 "\n{}1{}{}\n{}\n{}2{}{}\n{}\n",
                 HEAD_START,
@@ -395,4 +612,60 @@ eeeeeee";
             .replace('+', "\n")
         )
     }
+
+    #[test]
+    fn test_build_list_with_invalid_elements() {
+        //             0123456789012345678
+        let content = "aaaa+bbbb+cccc+dddd".replace('+', "\n");
+        let markers = [((10..14, None), true, create_origin(None))];
+        let invalid_elements = [InvalidElement {
+            tag_name: "tl".to_string(),
+            attributes: vec![],
+            byte_range: 5..9,
+            parent: None,
+            error: EvaluationError::MissingAttribute("to".to_string()),
+        }];
+        let line_map = build_line_map(&content);
+
+        assert_eq!(
+            build_list(&content, &markers, &invalid_elements, Some(&line_map)),
+            vec![
+                ListItem {
+                    line_range: Some((2, 2)),
+                    annotated_code_block: "         _start\n      2 |bbbb\n            ‾end"
+                        .to_string(),
+                    current_status: ItemStatus::Invalid,
+                    message: Some("<tl> The attribute \"to\" is required".to_string()),
+                    tag_name: "tl".to_string(),
+                    attributes: vec![],
+                    strategy: None,
+                    byte_range: (5, 9),
+                    char_range: (5, 9),
+                    start_column: Some(1),
+                    end_column: Some(4),
+                    expires_at: None,
+                    parent: None,
+                },
+                ListItem {
+                    line_range: Some((3, 3)),
+                    annotated_code_block: "         _start\n      3 |cccc\n            ‾end"
+                        .to_string(),
+                    current_status: ItemStatus::Ready,
+                    message: None,
+                    tag_name: "tl".to_string(),
+                    attributes: vec![ListItemAttribute {
+                        name: "to".to_string(),
+                        value: Some("2000-01-01".to_string()),
+                    }],
+                    strategy: Some("range"),
+                    byte_range: (10, 14),
+                    char_range: (10, 14),
+                    start_column: Some(1),
+                    end_column: Some(4),
+                    expires_at: Some("2000-01-01T00:00:00+09:00".to_string()),
+                    parent: None,
+                },
+            ]
+        )
+    }
 }
@@ -1,13 +1,17 @@
 pub mod marker;
 pub mod removal_evaluator;
 
+use crate::diagnostic::{Diagnostic, Severity, Span};
 use crate::element_parser::Element;
 use crate::parser;
 use crate::parser::ContentPart;
+use chrono::{DateTime, FixedOffset};
+use marker::builder::skip_marker_builder::SKIP_STRATEGY_NAME;
 use marker::factory::{create, RemovableRange, RemoveStrategies};
-use removal_evaluator::RemovalEvaluator;
+use removal_evaluator::{EvaluationError, RemovalEvaluator};
 use std::collections::HashMap;
 use std::ops::Range;
+use thiserror::Error;
 
 pub type RemoveMarker = (Range<usize>, Option<usize>);
 pub type RemovedMarker = (usize, Option<usize>);
@@ -16,9 +20,72 @@ type RemovalEvaluators = HashMap<String, Box<dyn RemovalEvaluator>>;
 
 struct RemovalRangeTree {
     range: RemovableRange,
+    origin: MarkerOrigin,
+    /// Indents removed from the lines between the paired ranges
+    dedent_ranges: Vec<Range<usize>>,
     children: Vec<RemovalRangeTree>,
 }
 
+/// The element from which a marker is built
+#[derive(Debug, PartialEq, Clone)]
+pub struct MarkerOrigin {
+    pub tag_name: String,
+    /// Attributes in order of appearance (name, value)
+    pub attributes: Vec<(String, Option<String>)>,
+    /// The name of the strategy which built the marker (e.g. "range", "unwrap-block")
+    pub strategy: &'static str,
+    /// The date time when the element expires (only for time-based elements)
+    pub expires_at: Option<DateTime<FixedOffset>>,
+    /// The element enclosing this element
+    pub parent: Option<ParentElement>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ParentElement {
+    pub tag_name: String,
+    /// The byte range of the start tag
+    pub byte_range: Range<usize>,
+}
+
+/// An element whose removal cannot be evaluated because of missing or malformed attributes
+#[derive(Error, Debug, PartialEq, Clone)]
+#[error("<{tag_name}> {error}")]
+pub struct InvalidElement {
+    pub tag_name: String,
+    /// Attributes in order of appearance (name, value)
+    pub attributes: Vec<(String, Option<String>)>,
+    /// The byte range of the start tag
+    pub byte_range: Range<usize>,
+    /// The element enclosing this element
+    pub parent: Option<ParentElement>,
+    pub error: EvaluationError,
+}
+
+impl InvalidElement {
+    pub fn to_diagnostic(
+        &self,
+        severity: Severity,
+        content: &str,
+        line_map: &[usize],
+    ) -> Diagnostic {
+        Diagnostic::new(severity, self.error.code(), self.to_string()).with_span(Span::new(
+            content,
+            line_map,
+            self.byte_range.clone(),
+        ))
+    }
+}
+
+/// An element whose strategy finds nothing to remove
+#[derive(Debug, PartialEq, Clone)]
+pub struct IneffectiveElement {
+    pub tag_name: String,
+    /// The byte range of the start tag
+    pub byte_range: Range<usize>,
+    /// The name of the strategy (e.g. "unwrap-else")
+    pub strategy: &'static str,
+}
+
 pub struct Remover {
     removal_evaluators: RemovalEvaluators,
     remove_strategies: RemoveStrategies,
@@ -32,10 +99,31 @@ impl Remover {
         }
     }
 
+    /// Remove elements ready to be removed.
+    /// Paired markers are returned without the pair if the content between them keeps its indent,
+    /// so that it is not dedented by the formatters.
+    ///
+    /// The indents built by the strategies (e.g. unwrap-indent) are removed first,
+    /// so the markers are returned as the ranges in the dedented content.
     pub fn remove(&self, content: Vec<ContentPart>, raw: &str) -> (String, Vec<RemoveMarker>) {
-        let markers = self.build_remove_marker(&content);
+        let (ranges, _) = self.collect_removable_ranges(&content, raw, None, false);
+        let dedent_ranges = collect_dedent_ranges(&ranges);
+
+        let markers: Vec<RemoveMarker> = Self::merge_markers(ranges)
+            .into_iter()
+            .map(|((range, pair), origin)| {
+                (
+                    dedented_pos(range.start, &dedent_ranges)
+                        ..dedented_pos(range.end, &dedent_ranges),
+                    pair.filter(|_| !self.keeps_indent(origin.strategy)),
+                )
+            })
+            .collect();
         let mut new_content = raw.to_string();
 
+        for range in dedent_ranges.iter().rev() {
+            new_content.replace_range(range.clone(), "");
+        }
         for (marker, _) in markers.iter().rev() {
             new_content.replace_range(marker.clone(), "");
         }
@@ -43,13 +131,41 @@ impl Remover {
         (new_content, markers)
     }
 
-    pub fn build_remove_marker(&self, contents: &[ContentPart]) -> Vec<RemoveMarker> {
-        let (ranges, _) = self.collect_removable_ranges(contents, false);
+    pub fn build_remove_marker(&self, contents: &[ContentPart], raw: &str) -> Vec<RemoveMarker> {
+        self.build_remove_marker_with_origin(contents, raw)
+            .into_iter()
+            .map(|(marker, _)| marker)
+            .collect()
+    }
+
+    /// Build markers together with the elements from which they are built.
+    pub fn build_remove_marker_with_origin(
+        &self,
+        contents: &[ContentPart],
+        raw: &str,
+    ) -> Vec<(RemoveMarker, MarkerOrigin)> {
+        let (ranges, _) = self.collect_removable_ranges(contents, raw, None, false);
         Self::merge_markers(ranges)
     }
 
-    pub fn build_remove_marker_all(&self, contents: &[ContentPart]) -> Vec<(RemoveMarker, bool)> {
-        let (ranges, ranges_pending) = self.collect_removable_ranges(contents, true);
+    pub fn build_remove_marker_all(
+        &self,
+        contents: &[ContentPart],
+        raw: &str,
+    ) -> Vec<(RemoveMarker, bool)> {
+        self.build_remove_marker_all_with_origin(contents, raw)
+            .into_iter()
+            .map(|(marker, is_removal, _)| (marker, is_removal))
+            .collect()
+    }
+
+    /// Build markers including pending ones together with the elements from which they are built.
+    pub fn build_remove_marker_all_with_origin(
+        &self,
+        contents: &[ContentPart],
+        raw: &str,
+    ) -> Vec<(RemoveMarker, bool, MarkerOrigin)> {
+        let (ranges, ranges_pending) = self.collect_removable_ranges(contents, raw, None, true);
         let ranges = Self::merge_markers(ranges);
         let ranges_pending = Self::merge_markers(ranges_pending);
 
@@ -65,11 +181,11 @@ impl Remover {
         // ---------------------------------------------------------
         let mut merged_ranges = Vec::new();
         let mut range_cursor = 0;
-        for (range, idx) in ranges {
+        for ((range, idx), origin) in ranges {
             let item = {
                 // Pop item from pending_ranges
                 if range_cursor < ranges_pending.len() {
-                    let (pending_range, idx) = &ranges_pending[range_cursor];
+                    let ((pending_range, idx), origin) = &ranges_pending[range_cursor];
 
                     if pending_range.start < range.end {
                         range_cursor += 1;
@@ -79,7 +195,7 @@ impl Remover {
                         if can_squash {
                             None
                         } else {
-                            Some((pending_range.clone(), *idx))
+                            Some(((pending_range.clone(), *idx), origin.clone()))
                         }
                     } else {
                         None
@@ -89,27 +205,123 @@ impl Remover {
                 }
             };
 
-            if let Some(item) = item {
-                merged_ranges.push((item, false));
+            if let Some((marker, origin)) = item {
+                merged_ranges.push((marker, false, origin));
             }
 
-            merged_ranges.push(((range.clone(), idx), true));
+            merged_ranges.push(((range.clone(), idx), true, origin));
         }
 
         if range_cursor < ranges_pending.len() {
             merged_ranges.extend(
                 ranges_pending[range_cursor..ranges_pending.len()]
                     .iter()
-                    .map(|v| (v.clone(), false)),
+                    .map(|(marker, origin)| (marker.clone(), false, origin.clone())),
             );
         }
 
         merged_ranges
     }
 
+    fn keeps_indent(&self, strategy: &str) -> bool {
+        self.remove_strategies
+            .iter()
+            .any(|(_, builder)| builder.strategy_name() == strategy && builder.keeps_indent())
+    }
+
+    fn dedent_ranges(&self, strategy: &str, raw: &str, el: &parser::Element) -> Vec<Range<usize>> {
+        self.remove_strategies
+            .iter()
+            .find(|(_, builder)| builder.strategy_name() == strategy)
+            .map(|(_, builder)| builder.dedent_ranges(raw, el))
+            .unwrap_or_default()
+    }
+
+    pub fn find_invalid_elements(&self, contents: &[ContentPart]) -> Vec<InvalidElement> {
+        self.find_invalid_elements_in(contents, None)
+    }
+
+    fn find_invalid_elements_in(
+        &self,
+        contents: &[ContentPart],
+        parent: Option<&parser::Element>,
+    ) -> Vec<InvalidElement> {
+        contents.iter().fold(vec![], |mut acc, c| {
+            if let parser::ContentPart::Element(el) = c {
+                if !is_skip(&el.start_element) {
+                    let error = self
+                        .removal_evaluators
+                        .get(el.start_element.name)
+                        .and_then(|evaluator| evaluator.is_removal(&el.start_element).err());
+
+                    if let Some(error) = error {
+                        acc.push(InvalidElement {
+                            tag_name: el.start_element.name.to_string(),
+                            attributes: collect_attributes(&el.start_element),
+                            byte_range: el.start_token.byte_start..el.start_token.byte_end,
+                            parent: parent.map(ParentElement::from),
+                            error,
+                        });
+                    }
+                }
+
+                acc.extend(self.find_invalid_elements_in(&el.children, Some(el)));
+            }
+
+            acc
+        })
+    }
+
+    /// Find elements whose strategy finds nothing to remove (e.g. `unwrap-else` without an else branch).
+    /// They are left as they are regardless of whether they are ready to be removed.
+    pub fn find_ineffective_elements(
+        &self,
+        contents: &[ContentPart],
+        raw: &str,
+    ) -> Vec<IneffectiveElement> {
+        self.find_ineffective_elements_in(contents, raw)
+    }
+
+    fn find_ineffective_elements_in(
+        &self,
+        contents: &[ContentPart],
+        raw: &str,
+    ) -> Vec<IneffectiveElement> {
+        contents.iter().fold(vec![], |mut acc, c| {
+            if let parser::ContentPart::Element(el) = c {
+                let is_evaluable = self
+                    .removal_evaluators
+                    .get(el.start_element.name)
+                    .is_some_and(|evaluator| evaluator.is_removal(&el.start_element).is_ok());
+
+                if is_evaluable && !is_skip(&el.start_element) {
+                    let ineffective_strategy = create(raw, el, &self.remove_strategies)
+                        .filter(|((range, _), strategy)| {
+                            range.is_empty() && *strategy != SKIP_STRATEGY_NAME
+                        })
+                        .map(|(_, strategy)| strategy);
+
+                    if let Some(strategy) = ineffective_strategy {
+                        acc.push(IneffectiveElement {
+                            tag_name: el.start_element.name.to_string(),
+                            byte_range: el.start_token.byte_start..el.start_token.byte_end,
+                            strategy,
+                        });
+                    }
+                }
+
+                acc.extend(self.find_ineffective_elements_in(&el.children, raw));
+            }
+
+            acc
+        })
+    }
+
     fn collect_removable_ranges(
         &self,
         contents: &[ContentPart],
+        raw: &str,
+        parent: Option<&parser::Element>,
         collect_pending_removals: bool,
     ) -> (Vec<RemovalRangeTree>, Vec<RemovalRangeTree>) {
         contents.iter().fold(
@@ -121,40 +333,60 @@ impl Remover {
                     } else {
                         self.removal_evaluators
                             .get(el.start_element.name)
-                            .and_then(|evaluator| match evaluator.is_removal(&el.start_element) {
-                                true => create(el, &self.remove_strategies).map(|f| (f, true)),
-                                false => {
-                                    if collect_pending_removals {
-                                        create(el, &self.remove_strategies).map(|f| (f, false))
-                                    } else {
-                                        None
-                                    }
-                                }
-                            })
-                            .and_then(|((range, closed_range), is_removal)| {
-                                if !range.is_empty() {
-                                    Some(((range, closed_range), is_removal))
-                                } else {
-                                    None
-                                }
+                            .and_then(|evaluator| {
+                                let is_removal = match evaluator.is_removal(&el.start_element) {
+                                    Ok(true) => true,
+                                    Ok(false) if collect_pending_removals => false,
+                                    Ok(false) => return None,
+                                    // Elements that cannot be evaluated are left as they are.
+                                    Err(_) => return None,
+                                };
+
+                                create(raw, el, &self.remove_strategies).map(|(range, strategy)| {
+                                    let origin = MarkerOrigin {
+                                        tag_name: el.start_element.name.to_string(),
+                                        attributes: collect_attributes(&el.start_element),
+                                        strategy,
+                                        expires_at: evaluator.expires_at(&el.start_element),
+                                        parent: parent.map(ParentElement::from),
+                                    };
+                                    let dedent_ranges = self.dedent_ranges(strategy, raw, el);
+                                    (range, origin, dedent_ranges, is_removal)
+                                })
                             })
+                            .filter(|((range, _), _, _, _)| !range.is_empty())
                     };
 
-                    let (children, pending_removal_children) =
-                        self.collect_removable_ranges(&el.children, collect_pending_removals);
-
-                    if let Some((range, true)) = range {
-                        removal_tree.push(RemovalRangeTree { range, children });
-                        pending_removal_tree.extend(pending_removal_children);
-                    } else if let Some((range, false)) = range {
-                        removal_tree.extend(children);
-                        pending_removal_tree.push(RemovalRangeTree {
-                            range,
-                            children: pending_removal_children,
-                        });
-                    } else {
-                        removal_tree.extend(children);
-                        pending_removal_tree.extend(pending_removal_children);
+                    let (children, pending_removal_children) = self.collect_removable_ranges(
+                        &el.children,
+                        raw,
+                        Some(el),
+                        collect_pending_removals,
+                    );
+
+                    match range {
+                        Some((range, origin, dedent_ranges, true)) => {
+                            removal_tree.push(RemovalRangeTree {
+                                range,
+                                origin,
+                                dedent_ranges,
+                                children,
+                            });
+                            pending_removal_tree.extend(pending_removal_children);
+                        }
+                        Some((range, origin, dedent_ranges, false)) => {
+                            removal_tree.extend(children);
+                            pending_removal_tree.push(RemovalRangeTree {
+                                range,
+                                origin,
+                                dedent_ranges,
+                                children: pending_removal_children,
+                            });
+                        }
+                        None => {
+                            removal_tree.extend(children);
+                            pending_removal_tree.extend(pending_removal_children);
+                        }
                     }
                 }
 
@@ -163,7 +395,7 @@ impl Remover {
         )
     }
 
-    fn merge_markers(ranges: Vec<RemovalRangeTree>) -> Vec<RemoveMarker> {
+    fn merge_markers(ranges: Vec<RemovalRangeTree>) -> Vec<(RemoveMarker, MarkerOrigin)> {
         ranges.into_iter().fold(vec![], |mut acc, tree| {
             let child_markers = Self::merge_markers(tree.children);
             let (mut marker, pair) = tree.range;
@@ -176,15 +408,15 @@ impl Remover {
 
                 let current = acc.len();
                 acc.push((
-                    marker,
-                    Some(current + (end_cursor - start_cursor).max(0) + 1),
+                    (marker, Some(current + (end_cursor - start_cursor) + 1)),
+                    tree.origin.clone(),
                 ));
                 if start_cursor < end_cursor {
                     acc.extend(child_markers[start_cursor..end_cursor].to_owned());
                 }
-                acc.push((end_marker, Some(current)));
+                acc.push(((end_marker, Some(current)), tree.origin));
             } else {
-                acc.push((marker, None));
+                acc.push(((marker, None), tree.origin));
             }
 
             acc
@@ -193,10 +425,10 @@ impl Remover {
 
     fn merge_child_markers<'a, T>(child_markers: T, marker: &mut Range<usize>) -> usize
     where
-        T: Iterator<Item = &'a RemoveMarker>,
+        T: Iterator<Item = &'a (RemoveMarker, MarkerOrigin)>,
     {
         let mut cursor = 0;
-        for (child_marker, _) in child_markers {
+        for ((child_marker, _), _) in child_markers {
             if marker.contains(&child_marker.start) || marker.contains(&child_marker.end) {
                 marker.start = marker.start.min(child_marker.start);
                 marker.end = marker.end.max(child_marker.end);
@@ -210,6 +442,44 @@ impl Remover {
     }
 }
 
+impl From<&parser::Element<'_, '_, '_, '_>> for ParentElement {
+    fn from(el: &parser::Element) -> Self {
+        Self {
+            tag_name: el.start_element.name.to_string(),
+            byte_range: el.start_token.byte_start..el.start_token.byte_end,
+        }
+    }
+}
+
+/// Collect the indents to be removed in ascending order without overlaps.
+fn collect_dedent_ranges(ranges: &[RemovalRangeTree]) -> Vec<Range<usize>> {
+    let mut dedent_ranges: Vec<Range<usize>> = vec![];
+    let mut stack: Vec<&RemovalRangeTree> = ranges.iter().collect();
+    while let Some(tree) = stack.pop() {
+        dedent_ranges.extend(tree.dedent_ranges.iter().cloned());
+        stack.extend(tree.children.iter());
+    }
+    dedent_ranges.sort_by_key(|range| range.start);
+
+    dedent_ranges
+        .into_iter()
+        .fold(vec![], |mut acc: Vec<Range<usize>>, range| {
+            match acc.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => acc.push(range),
+            }
+            acc
+        })
+}
+
+/// The position in the content from which the indents are removed
+fn dedented_pos(pos: usize, dedent_ranges: &[Range<usize>]) -> usize {
+    pos - dedent_ranges
+        .iter()
+        .map(|range| range.end.min(pos) - range.start.min(pos))
+        .sum::<usize>()
+}
+
 pub fn get_removed_pos(markers: &[RemoveMarker]) -> Vec<RemovedMarker> {
     markers
         .iter()
@@ -228,6 +498,13 @@ fn is_skip(el: &Element) -> bool {
     el.attrs.iter().any(|v| v.name == "skip")
 }
 
+fn collect_attributes(el: &Element) -> Vec<(String, Option<String>)> {
+    el.attrs
+        .iter()
+        .map(|a| (a.name.to_string(), a.value.as_ref().map(|v| v.to_string())))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use rstest::rstest;
@@ -235,8 +512,8 @@ mod tests {
 
     use marker::{
         availability::{
+            attribute_marker_availability::AttributeMarkerAvailability,
             range_marker_availability::RangeMarkerAvailability,
-            unwrap_block_marker_availability::UnwrapBlockMarkerAvailability,
         },
         builder::{
             range_marker_builder::RangeMarkerBuilder,
@@ -250,11 +527,11 @@ mod tests {
     use super::*;
     use crate::tokenizer;
 
-    fn initialize_remove_strategy(content: Rc<String>) -> RemoveStrategies {
+    fn initialize_remove_strategy() -> RemoveStrategies {
         vec![
             (
-                Box::new(UnwrapBlockMarkerAvailability::new("unwrap-block")),
-                Box::new(UnwrapBlockMarkerBuilder { content }),
+                Box::new(AttributeMarkerAvailability::new("unwrap-block")),
+                Box::new(UnwrapBlockMarkerBuilder::default()),
             ),
             (
                 Box::new(RangeMarkerAvailability::default()),
@@ -292,9 +569,8 @@ mod tests {
         );
         let tokens = tokenizer::tokenize(content, "<", ">");
         let contents = parser::parse(&tokens);
-        let content = Rc::new(content.to_string());
-        let remover = Remover::new(removal_evaluators, initialize_remove_strategy(content));
-        assert_eq!(remover.build_remove_marker(&contents), expected);
+        let remover = Remover::new(removal_evaluators, initialize_remove_strategy());
+        assert_eq!(remover.build_remove_marker(&contents, content), expected);
     }
 
     #[rstest]
@@ -316,9 +592,60 @@ mod tests {
         );
         let tokens = tokenizer::tokenize(content, "<", ">");
         let contents = parser::parse(&tokens);
-        let content = Rc::new(content.to_string());
-        let remover = Remover::new(removal_evaluators, initialize_remove_strategy(content));
-        assert_eq!(remover.build_remove_marker_all(&contents), expected);
+        let remover = Remover::new(removal_evaluators, initialize_remove_strategy());
+        assert_eq!(
+            remover.build_remove_marker_all(&contents, content),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_remove_marker_with_origin() {
+        //   0         1         2         3         4         5         6
+        //   0123456789012345678901234567890123456789012345678901234567890123
+        let content = Rc::new(
+            "<f name='b'>+<tl to='2000-01-01' unwrap-block>+{+a+}+</tl>+</f>".replace('+', "\n"),
+        );
+        let mut removal_evaluators = initialize_removal_evaluators();
+        removal_evaluators.insert(
+            String::from("f"),
+            Box::new(MarkerEvaluator {
+                marker_removal_names: [String::from("a")].into(),
+            }),
+        );
+        let tokens = tokenizer::tokenize(&content, "<", ">");
+        let contents = parser::parse(&tokens);
+        let remover = Remover::new(removal_evaluators, initialize_remove_strategy());
+
+        let tl_origin = MarkerOrigin {
+            tag_name: "tl".to_string(),
+            attributes: vec![
+                ("to".to_string(), Some("2000-01-01".to_string())),
+                ("unwrap-block".to_string(), None),
+            ],
+            strategy: "unwrap-block",
+            expires_at: Some(chrono::DateTime::parse_from_rfc3339("2000-01-01T00:00:00Z").unwrap()),
+            parent: Some(ParentElement {
+                tag_name: "f".to_string(),
+                byte_range: 0..12,
+            }),
+        };
+        let f_origin = MarkerOrigin {
+            tag_name: "f".to_string(),
+            attributes: vec![("name".to_string(), Some("b".to_string()))],
+            strategy: "range",
+            expires_at: None,
+            parent: None,
+        };
+
+        assert_eq!(
+            remover.build_remove_marker_all_with_origin(&contents, &content),
+            vec![
+                ((0..63, None), false, f_origin),
+                ((13..48, Some(1)), true, tl_origin.clone()),
+                ((51..58, Some(0)), true, tl_origin),
+            ]
+        );
     }
 
     #[test]
@@ -342,7 +669,7 @@ mod tests {
         );
         let remover = Remover::new(
             initialize_removal_evaluators(),
-            initialize_remove_strategy(Rc::clone(&content)),
+            initialize_remove_strategy(),
         );
         let (removed, markers) = remover.remove(
             parser::parse(&tokenizer::tokenize(&content, "<!--", "-->")),
@@ -369,7 +696,7 @@ foo
         );
         let remover = Remover::new(
             initialize_removal_evaluators(),
-            initialize_remove_strategy(Rc::clone(&content)),
+            initialize_remove_strategy(),
         );
         let (removed, markers) = remover.remove(
             parser::parse(&tokenizer::tokenize(&content, "<!--", "-->")),
@@ -397,7 +724,7 @@ foo
         );
         let remover = Remover::new(
             initialize_removal_evaluators(),
-            initialize_remove_strategy(Rc::clone(&content)),
+            initialize_remove_strategy(),
         );
         let (removed, _) = remover.remove(
             parser::parse(&tokenizer::tokenize(&content, "<!--", "-->")),
@@ -420,7 +747,7 @@ if (foo) {
         );
         let remover = Remover::new(
             initialize_removal_evaluators(),
-            initialize_remove_strategy(Rc::clone(&content)),
+            initialize_remove_strategy(),
         );
         let (removed, markers) = remover.remove(
             parser::parse(&tokenizer::tokenize(&content, "/*", "*/")),
@@ -446,7 +773,7 @@ if (foo) {
         let content = Rc::new("foo+<tl to='2021-01-01 00:00:00'>+bar+</tl>+<tl to='2000-01-01 00:00:00' unwrap-block>+{+  s1+  s2+}+</tl>+<tl to='2021-01-01 00:00:00'>+bar+</tl>".replace("+", "\n"));
         let remover = Remover::new(
             initialize_removal_evaluators(),
-            initialize_remove_strategy(Rc::clone(&content)),
+            initialize_remove_strategy(),
         );
         let (removed, markers) = remover.remove(
             parser::parse(&tokenizer::tokenize(&content, "<", ">")),
@@ -469,7 +796,7 @@ if (foo) {
         let content = Rc::new("foo+<tl to='2021-01-01 00:00:00'>+bar+</tl>+<tl to='2000-01-01 00:00:00' unwrap-block>+{+  s1+  <tl to='2021-01-01 00:00:00'>+  bar+  </tl>+  s2+}+</tl>".replace("+", "\n"));
         let remover = Remover::new(
             initialize_removal_evaluators(),
-            initialize_remove_strategy(Rc::clone(&content)),
+            initialize_remove_strategy(),
         );
         let (removed, markers) = remover.remove(
             parser::parse(&tokenizer::tokenize(&content, "<", ">")),
@@ -490,6 +817,40 @@ if (foo) {
         );
     }
 
+    #[test]
+    fn test_find_invalid_elements() {
+        //             0         1         2         3         4         5
+        //             012345678901234567890123456789012345678901234567890123456789
+        let content = "<tl to='2000-01-01'><tl to='foo'>a</tl></tl><tl skip>b</tl>";
+        let tokens = tokenizer::tokenize(content, "<", ">");
+        let contents = parser::parse(&tokens);
+        let remover = Remover::new(
+            initialize_removal_evaluators(),
+            initialize_remove_strategy(),
+        );
+
+        assert_eq!(
+            remover.find_invalid_elements(&contents),
+            vec![InvalidElement {
+                tag_name: "tl".to_string(),
+                attributes: vec![("to".to_string(), Some("foo".to_string()))],
+                byte_range: 20..33,
+                parent: Some(ParentElement {
+                    tag_name: "tl".to_string(),
+                    byte_range: 0..20,
+                }),
+                error: EvaluationError::InvalidAttribute {
+                    name: "to".to_string(),
+                    reason: "Invalid date time: \"foo\"".to_string()
+                }
+            }]
+        );
+        assert_eq!(
+            remover.build_remove_marker(&contents, content),
+            vec![(0..44, None)]
+        );
+    }
+
     #[test]
     fn test_removed_pos() {
         // cursor_pos       :0 1 2 3 4 5 6 7 8 9 A B
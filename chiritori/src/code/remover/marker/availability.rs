@@ -1,5 +1,7 @@
+pub mod attribute_marker_availability;
 pub mod range_marker_availability;
-pub mod unwrap_block_marker_availability;
+pub mod tag_name_marker_availability;
+pub mod time_released_marker_availability;
 
 use crate::parser::Element;
 
@@ -0,0 +1,22 @@
+use super::MarkerAvailability;
+
+/// Available for elements with the attribute (e.g. `unwrap-block`, `unwrap-else`, `unwrap-indent` or `strip-tags`)
+pub struct AttributeMarkerAvailability {
+    attribute_name: &'static str,
+}
+
+impl AttributeMarkerAvailability {
+    pub fn new(attribute_name: &'static str) -> Self {
+        Self { attribute_name }
+    }
+}
+
+impl MarkerAvailability for AttributeMarkerAvailability {
+    fn is_available(&self, element: &crate::parser::Element) -> bool {
+        element
+            .start_element
+            .attrs
+            .iter()
+            .any(|a| a.name == self.attribute_name)
+    }
+}
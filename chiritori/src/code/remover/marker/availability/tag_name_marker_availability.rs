@@ -0,0 +1,77 @@
+use super::MarkerAvailability;
+use std::collections::HashSet;
+
+/// Decides the availability by the tag name for the tags configured to use a strategy,
+/// and by the inner availability (e.g. an attribute) for the other tags.
+///
+/// ```text
+///  <tag> configured to use this strategy     -> available
+///  <tag> configured to use another strategy  -> not available
+///  otherwise                                 -> the inner availability
+/// ```
+pub struct TagNameMarkerAvailability {
+    inner: Box<dyn MarkerAvailability>,
+    tag_names: HashSet<String>,
+    other_tag_names: HashSet<String>,
+}
+
+impl TagNameMarkerAvailability {
+    pub fn new(
+        inner: Box<dyn MarkerAvailability>,
+        tag_names: HashSet<String>,
+        other_tag_names: HashSet<String>,
+    ) -> Self {
+        Self {
+            inner,
+            tag_names,
+            other_tag_names,
+        }
+    }
+}
+
+impl MarkerAvailability for TagNameMarkerAvailability {
+    fn is_available(&self, element: &crate::parser::Element) -> bool {
+        let tag_name = element.start_element.name;
+
+        if self.tag_names.contains(tag_name) {
+            true
+        } else if self.other_tag_names.contains(tag_name) {
+            false
+        } else {
+            self.inner.is_available(element)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code::remover::marker::availability::attribute_marker_availability::AttributeMarkerAvailability;
+    use crate::{parser, tokenizer};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("<a>x</a>", true)]
+    #[case("<b strip-tags>x</b>", false)]
+    #[case("<c strip-tags>x</c>", true)]
+    #[case("<c>x</c>", false)]
+    fn test_is_available(#[case] content: &str, #[case] expected: bool) {
+        let availability = TagNameMarkerAvailability::new(
+            Box::new(AttributeMarkerAvailability::new("strip-tags")),
+            HashSet::from(["a".to_string()]),
+            HashSet::from(["b".to_string()]),
+        );
+
+        let tokens = tokenizer::tokenize(content, "<", ">");
+        let parsed = parser::parse(&tokens);
+        let element = parsed
+            .iter()
+            .find_map(|c| match c {
+                parser::ContentPart::Element(el) => Some(el),
+                _ => None,
+            })
+            .unwrap();
+
+        assert_eq!(availability.is_available(element), expected);
+    }
+}
@@ -0,0 +1,73 @@
+use super::MarkerAvailability;
+use crate::code::remover::removal_evaluator::time_released_evaluator::TimeReleasedEvaluator;
+
+/// Available for time-released elements whose content is kept,
+/// that is, released ones and pending ones (the content is revealed at the release date).
+/// Elements removed before the release are left to the other strategies.
+pub struct TimeReleasedMarkerAvailability {
+    tag_name: String,
+    evaluator: TimeReleasedEvaluator,
+}
+
+impl TimeReleasedMarkerAvailability {
+    pub fn new(tag_name: impl Into<String>, evaluator: TimeReleasedEvaluator) -> Self {
+        Self {
+            tag_name: tag_name.into(),
+            evaluator,
+        }
+    }
+}
+
+impl MarkerAvailability for TimeReleasedMarkerAvailability {
+    fn is_available(&self, element: &crate::parser::Element) -> bool {
+        element.start_element.name == self.tag_name
+            && (!self.evaluator.remove_before_release
+                || self
+                    .evaluator
+                    .is_released(&element.start_element)
+                    .unwrap_or(false))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parser, tokenizer};
+    use chrono::DateTime;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("<time-released from=\"2021-12-31\">a</time-released>", false, true)]
+    #[case("<time-released from=\"2022-01-02\">a</time-released>", false, true)]
+    #[case("<time-released from=\"2021-12-31\">a</time-released>", true, true)]
+    #[case("<time-released from=\"2022-01-02\">a</time-released>", true, false)]
+    #[case("<time-limited to=\"2021-12-31\">a</time-limited>", false, false)]
+    fn test_is_available(
+        #[case] content: &str,
+        #[case] remove_before_release: bool,
+        #[case] expected: bool,
+    ) {
+        let availability = TimeReleasedMarkerAvailability::new(
+            "time-released",
+            TimeReleasedEvaluator {
+                current_time: DateTime::parse_from_rfc3339("2022-01-01T00:00:00+00:00")
+                    .unwrap()
+                    .into(),
+                time_offset: "+00:00".to_string(),
+                remove_before_release,
+            },
+        );
+
+        let tokens = tokenizer::tokenize(content, "<", ">");
+        let parsed = parser::parse(&tokens);
+        let element = parsed
+            .iter()
+            .find_map(|c| match c {
+                parser::ContentPart::Element(el) => Some(el),
+                _ => None,
+            })
+            .unwrap();
+
+        assert_eq!(availability.is_available(element), expected);
+    }
+}
@@ -1,9 +1,29 @@
 pub mod range_marker_builder;
+pub mod skip_marker_builder;
+pub mod strip_tags_marker_builder;
+#[cfg(feature = "tree-sitter")]
+pub mod syntax_unwrap_block_marker_builder;
 pub mod unwrap_block_marker_builder;
+pub mod unwrap_else_marker_builder;
+pub mod unwrap_indent_marker_builder;
 
 use crate::parser::Element;
 use std::ops::Range;
 
 pub trait MarkerBuilder {
-    fn build(&self, element: &Element) -> (Range<usize>, Option<Range<usize>>);
+    /// Build the range to be removed from the element.
+    /// `content` is the whole source code in which the element is found.
+    fn build(&self, content: &str, element: &Element) -> (Range<usize>, Option<Range<usize>>);
+    /// The name of the strategy shown in the list (e.g. "range")
+    fn strategy_name(&self) -> &'static str;
+    /// Whether the content between the paired ranges keeps its indent.
+    /// By default, it is dedented as the body of the removed block (e.g. unwrap-block).
+    fn keeps_indent(&self) -> bool {
+        false
+    }
+    /// Ranges of the indents removed from the lines between the paired ranges (e.g. one level of indentation).
+    /// They are removed together with the ranges built by `build` if the element is removed.
+    fn dedent_ranges(&self, _content: &str, _element: &Element) -> Vec<Range<usize>> {
+        vec![]
+    }
 }
@@ -6,9 +6,13 @@ use std::ops::Range;
 pub struct RangeMarkerBuilder {}
 
 impl MarkerBuilder for RangeMarkerBuilder {
-    fn build(&self, el: &Element) -> (Range<usize>, Option<Range<usize>>) {
+    fn build(&self, _content: &str, el: &Element) -> (Range<usize>, Option<Range<usize>>) {
         (el.start_token.byte_start..el.end_token.byte_end, None)
     }
+
+    fn strategy_name(&self) -> &'static str {
+        "range"
+    }
 }
 
 #[cfg(test)]
@@ -41,7 +45,7 @@ mod tests {
             })
             .unwrap();
 
-        let built = builder.build(&parsed);
+        let built = builder.build(&content, &parsed);
         assert_eq!(built, (8..32, None));
     }
 }
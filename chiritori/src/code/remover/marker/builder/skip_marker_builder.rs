@@ -0,0 +1,44 @@
+use super::MarkerBuilder;
+use crate::parser::Element;
+use std::ops::Range;
+
+pub const SKIP_STRATEGY_NAME: &str = "skip";
+
+/// Build an empty range so that nothing is removed.
+/// It is used in place of a disabled strategy.
+#[derive(Default)]
+pub struct SkipMarkerBuilder {}
+
+impl MarkerBuilder for SkipMarkerBuilder {
+    fn build(&self, _content: &str, el: &Element) -> (Range<usize>, Option<Range<usize>>) {
+        (el.start_token.byte_start..el.start_token.byte_start, None)
+    }
+
+    fn strategy_name(&self) -> &'static str {
+        SKIP_STRATEGY_NAME
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parser, tokenizer};
+
+    #[test]
+    fn test_build() {
+        let content = "foo+bar+<remove>+a+</remove>+baz".replace('+', "\n");
+
+        let builder = SkipMarkerBuilder::default();
+
+        let tokens = tokenizer::tokenize(&content, "<", ">");
+        let parsed = parser::parse(&tokens)
+            .into_iter()
+            .find_map(|c| match c {
+                parser::ContentPart::Element(el) => Some(el),
+                _ => None,
+            })
+            .unwrap();
+
+        assert_eq!(builder.build(&content, &parsed), (8..8, None));
+    }
+}
@@ -0,0 +1,131 @@
+use super::MarkerBuilder;
+use crate::parser::Element;
+use std::ops::Range;
+
+/// Build the ranges of the start and end tags so that only the tags are removed and the content is kept.
+/// A tag alone on its line is removed with the line, and the content is not re-indented.
+///
+/// ```text
+///  source:  a+..<remove>+..b+..</remove>+c   ->   a+..b+c
+///             ^-----------^    ^---------^
+///
+///  source:  a <remove>b</remove> c           ->   a b c
+///             ^------^ ^-------^
+/// ```
+#[derive(Default)]
+pub struct StripTagsMarkerBuilder {}
+
+impl MarkerBuilder for StripTagsMarkerBuilder {
+    fn build(&self, content: &str, el: &Element) -> (Range<usize>, Option<Range<usize>>) {
+        let bytes = content.as_bytes();
+
+        (
+            tag_line_range(bytes, el.start_token.byte_start..el.start_token.byte_end),
+            Some(tag_line_range(
+                bytes,
+                el.end_token.byte_start..el.end_token.byte_end,
+            )),
+        )
+    }
+
+    fn strategy_name(&self) -> &'static str {
+        "strip-tags"
+    }
+
+    fn keeps_indent(&self) -> bool {
+        true
+    }
+}
+
+/// Extend the range of the tag to the whole line (with the indent and the line break)
+/// if nothing else is on the line.
+fn tag_line_range(bytes: &[u8], tag: Range<usize>) -> Range<usize> {
+    let indent_len = bytes[..tag.start]
+        .iter()
+        .rev()
+        .take_while(|b| matches!(b, b' ' | b'\t'))
+        .count();
+    let line_start = tag.start - indent_len;
+    if line_start != 0 && bytes[line_start - 1] != b'\n' {
+        return tag;
+    }
+
+    // A tag closed at the end of the line (e.g. `# <tag>`) contains the line break.
+    if bytes[..tag.end].ends_with(b"\n") {
+        return line_start..tag.end;
+    }
+
+    let trailing_len = bytes[tag.end..]
+        .iter()
+        .take_while(|b| matches!(b, b' ' | b'\t' | b'\r'))
+        .count();
+    let line_end = tag.end + trailing_len;
+    match bytes.get(line_end) {
+        Some(b'\n') => line_start..line_end + 1,
+        None => line_start..line_end,
+        _ => tag,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+    use crate::tokenizer::{self, END_OF_LINE};
+    use rstest::rstest;
+
+    #[rstest]
+    //             10        20        30
+    //     012345678901234567890123456789012345
+    //     |       ^--------^   ^---------^
+    #[case("foo+bar+<remove>+a+b+</remove>+baz", 8..17, 21..31)]
+    //     |   ^-----------^  ^----------^
+    #[case("foo+  <remove>  +a+  </remove>", 4..17, 19..30)]
+    //     |   ^------^ ^-------^
+    #[case("foo <remove>a</remove> bar", 4..12, 13..22)]
+    //     |   ^------^  ^---------^
+    #[case("foo <remove>+a+</remove>+bar", 4..12, 15..25)]
+    #[case("foo+<remove>+a+</remove>", 4..13, 15..24)]
+    fn test_build(
+        #[case] input: &str,
+        #[case] expected_start_range: Range<usize>,
+        #[case] expected_end_range: Range<usize>,
+    ) {
+        let content = input.replace('+', "\n");
+
+        let builder = StripTagsMarkerBuilder::default();
+
+        let tokens = tokenizer::tokenize(&content, "<", ">");
+        let parsed = parser::parse(&tokens)
+            .into_iter()
+            .find_map(|c| match c {
+                parser::ContentPart::Element(el) => Some(el),
+                _ => None,
+            })
+            .unwrap();
+
+        assert_eq!(
+            builder.build(&content, &parsed),
+            (expected_start_range, Some(expected_end_range))
+        );
+    }
+
+    #[test]
+    fn test_build_with_crlf_and_end_of_line_tag() {
+        // '+' is CRLF, which is a part of the tags closed at the end of the line.
+        let content = "a+  # <r>+b+  # </r>+c".replace('+', "\r\n");
+
+        let builder = StripTagsMarkerBuilder::default();
+
+        let tokens = tokenizer::tokenize(&content, "# <", END_OF_LINE);
+        let parsed = parser::parse(&tokens)
+            .into_iter()
+            .find_map(|c| match c {
+                parser::ContentPart::Element(el) => Some(el),
+                _ => None,
+            })
+            .unwrap();
+
+        assert_eq!(builder.build(&content, &parsed), (3..12, Some(15..25)));
+    }
+}
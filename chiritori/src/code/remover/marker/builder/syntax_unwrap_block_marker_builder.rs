@@ -0,0 +1,241 @@
+use super::{unwrap_block_marker_builder::UnwrapBlockMarkerBuilder, MarkerBuilder};
+use crate::{
+    code::{
+        syntax::{find_block, SyntaxLanguage},
+        utils::line_break_pos_finder::{line_break_len_at, line_break_start},
+    },
+    parser::Element,
+};
+use std::{cell::RefCell, ops::Range};
+use tree_sitter::Tree;
+
+/// Unwraps the block using the syntax tree of the language.
+///
+/// The statement between the tags (e.g. `if`, `for` or a bare block) is found from the syntax tree,
+/// so the header of multi-line conditions, braces on their own lines and `else` branches are removed exactly.
+///
+/// ```text
+///   /* <tag unwrap-block> */
+///   ^
+///   if (
+///     a && b
+///   )
+///   {
+///    ^ (the line break after "{" is kept)
+///     foo();
+///   } else {
+///   ^
+///     bar();
+///   }
+///   /* </tag> */
+///               ^
+/// ```
+///
+/// If the statement is not found (e.g. the tags contain several statements), it falls back to `UnwrapBlockMarkerBuilder`.
+pub struct SyntaxUnwrapBlockMarkerBuilder {
+    language: SyntaxLanguage,
+    fallback: UnwrapBlockMarkerBuilder,
+    /// The syntax tree of the last content, so that it is parsed once for all elements of the content
+    tree: RefCell<Option<(String, Option<Tree>)>>,
+}
+
+impl SyntaxUnwrapBlockMarkerBuilder {
+    pub fn new(language: SyntaxLanguage) -> Self {
+        Self {
+            language,
+            fallback: UnwrapBlockMarkerBuilder::default(),
+            tree: RefCell::new(None),
+        }
+    }
+
+    fn build_with_syntax(
+        &self,
+        content: &str,
+        el: &Element,
+    ) -> Option<(Range<usize>, Option<Range<usize>>)> {
+        let mut cache = self.tree.borrow_mut();
+        if cache
+            .as_ref()
+            .is_none_or(|(cached_content, _)| cached_content != content)
+        {
+            *cache = Some((content.to_string(), self.language.parse(content)));
+        }
+        let tree = cache.as_ref()?.1.as_ref()?;
+
+        let block = find_block(
+            tree,
+            content,
+            el.start_token.byte_end..el.end_token.byte_start,
+        )?;
+
+        let bytes = content.as_bytes();
+        let keep_start = body_start(bytes, block.body.start);
+        let keep_end = body_end(bytes, block.body.end);
+        if keep_start < el.start_token.byte_end || keep_end < keep_start {
+            return None;
+        }
+
+        Some((
+            el.start_token.byte_start..keep_start,
+            Some(keep_end..el.end_token.byte_end),
+        ))
+    }
+}
+
+impl MarkerBuilder for SyntaxUnwrapBlockMarkerBuilder {
+    fn build(&self, content: &str, el: &Element) -> (Range<usize>, Option<Range<usize>>) {
+        self.build_with_syntax(content, el)
+            .unwrap_or_else(|| self.fallback.build(content, el))
+    }
+
+    fn strategy_name(&self) -> &'static str {
+        "unwrap-block"
+    }
+}
+
+/// The start of the content to be kept.
+/// If the body starts on a new line, the line break before it is kept as `UnwrapBlockMarkerBuilder` does.
+fn body_start(bytes: &[u8], pos: usize) -> usize {
+    let next = skip_spaces(bytes, pos);
+    if line_break_len_at(bytes, next).is_some() {
+        return next;
+    }
+
+    // A block without braces (e.g. Python) starts at the first statement.
+    let line_start = skip_spaces_backward(bytes, next);
+    if line_start > 0 && bytes[line_start - 1] == b'\n' {
+        line_break_start(bytes, line_start - 1)
+    } else {
+        next
+    }
+}
+
+/// The end of the content to be kept. The line break after the body is kept.
+fn body_end(bytes: &[u8], pos: usize) -> usize {
+    let prev = skip_spaces_backward(bytes, pos);
+    if prev > 0 && bytes[prev - 1] == b'\n' {
+        return prev;
+    }
+
+    // A block without braces (e.g. Python) ends at the last statement.
+    let next = skip_spaces(bytes, pos);
+    match line_break_len_at(bytes, next) {
+        Some(len) => next + len,
+        None => prev,
+    }
+}
+
+fn skip_spaces(bytes: &[u8], pos: usize) -> usize {
+    pos + bytes[pos..]
+        .iter()
+        .take_while(|b| matches!(b, b' ' | b'\t'))
+        .count()
+}
+
+fn skip_spaces_backward(bytes: &[u8], pos: usize) -> usize {
+    pos - bytes[..pos]
+        .iter()
+        .rev()
+        .take_while(|b| matches!(b, b' ' | b'\t'))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parser, tokenizer};
+    use rstest::rstest;
+
+    fn build(
+        language: SyntaxLanguage,
+        content: &str,
+        delimiter_start: &str,
+        delimiter_end: &str,
+    ) -> (Range<usize>, Option<Range<usize>>) {
+        let tokens = tokenizer::tokenize(content, delimiter_start, delimiter_end);
+        let parsed = parser::parse(&tokens)
+            .into_iter()
+            .find_map(|c| match c {
+                parser::ContentPart::Element(el) => Some(el),
+                _ => None,
+            })
+            .unwrap();
+
+        SyntaxUnwrapBlockMarkerBuilder::new(language).build(content, &parsed)
+    }
+
+    #[rstest]
+    // A brace on its own line and a multi-line condition
+    //             10        20        30
+    //     0123456789012345678901234567890123456789
+    //     ^-------------------^       ^---------^
+    #[case("/*<r>*/+if (+  a+)+{+  b();+}+/*</r>*/", 0..20, Some(28..38))]
+    // The else branch is removed with the closing brace.
+    //     ^---------------^       ^-------------------------^
+    #[case("/*<r>*/+if (a) {+  b();+} else {+  c();+}+/*</r>*/", 0..16, Some(24..50))]
+    // A single-line block
+    //     ^----------------^   ^----------^
+    #[case("/*<r>*/ if (a) { b(); } /*</r>*/", 0..17, Some(21..32))]
+    // Several statements fall back to the heuristic.
+    //     ^-----------^              ^---------^
+    #[case("/*<r>*/+a();+if (b) {+c();+}+/*</r>*/", 0..12, Some(27..37))]
+    fn test_build_javascript(
+        #[case] input: &str,
+        #[case] expected_start_range: Range<usize>,
+        #[case] expected_end_range: Option<Range<usize>>,
+    ) {
+        let content = input.replace('+', "\n");
+
+        assert_eq!(
+            build(SyntaxLanguage::JavaScript, &content, "/*<", ">*/"),
+            (expected_start_range, expected_end_range)
+        );
+    }
+
+    #[test]
+    fn test_build_python() {
+        // The header line is removed, and the body is kept with the line break after it.
+        //                   10        20        30
+        //         0123456789012345678901234567890123456
+        //         ^---------^                ^-----^
+        let content = "#<r>\nif a:\n    b()\n    c()\n#</r>\nd()\n";
+
+        assert_eq!(
+            build(SyntaxLanguage::Python, content, "#<", "\n"),
+            (0..10, Some(27..33))
+        );
+    }
+
+    #[test]
+    fn test_build_with_cached_tree() {
+        let builder = SyntaxUnwrapBlockMarkerBuilder::new(SyntaxLanguage::JavaScript);
+        let build_all = |content: &String| {
+            let tokens = tokenizer::tokenize(content, "/*<", ">*/");
+            parser::parse(&tokens)
+                .into_iter()
+                .filter_map(|c| match c {
+                    parser::ContentPart::Element(el) => Some(builder.build(content, &el)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // The tree parsed for the first element is reused for the second one.
+        let mut content = "/*<r>*/+if (a) {+  b();+}+/*</r>*/+/*<r>*/+if (c) {+  d();+}+/*</r>*/"
+            .replace('+', "\n");
+        assert_eq!(
+            build_all(&content),
+            vec![(0..16, Some(24..34)), (35..51, Some(59..69))]
+        );
+
+        // Another content is parsed again even if it has the same address and length.
+        let other = "/*<r>*/+while (abcde) {+  b();+}+/*</r>*/+/*<r>*/+{+  d();+}+/*</r>*/"
+            .replace('+', "\n");
+        assert_eq!(other.len(), content.len());
+        content.replace_range(.., &other);
+        assert_eq!(
+            build_all(&content),
+            vec![(0..23, Some(31..41)), (42..51, Some(59..69))]
+        );
+    }
+}
@@ -1,32 +1,35 @@
 use super::MarkerBuilder;
 use crate::{
-    code::utils::line_break_pos_finder::{find_next_line_break_pos, find_prev_line_break_pos},
+    code::utils::line_break_pos_finder::{
+        find_next_line_break_pos, find_prev_line_break_pos, line_break_start,
+    },
     parser::Element,
 };
-use std::{ops::Range, rc::Rc};
+use std::ops::Range;
 
-pub struct UnwrapBlockMarkerBuilder {
-    pub content: Rc<String>,
-}
+#[derive(Default)]
+pub struct UnwrapBlockMarkerBuilder {}
 
 impl MarkerBuilder for UnwrapBlockMarkerBuilder {
-    fn build(&self, el: &Element) -> (Range<usize>, Option<Range<usize>>) {
-        let bytes = self.content.as_bytes();
-        let start_el_remove_end_pos =
-            find_next_line_break_pos(self.content.as_ref(), bytes, el.start_token.byte_end, false)
-                .and_then(|pos| {
-                    find_next_line_break_pos(self.content.as_ref(), bytes, pos + 1, false)
-                });
+    fn build(&self, content: &str, el: &Element) -> (Range<usize>, Option<Range<usize>>) {
+        let bytes = content.as_bytes();
+        // A tag closed at the end of the line (e.g. `// <tag>`) already contains the line break of its line.
+        let start_el_line_end = match el.start_token.value.ends_with('\n') {
+            true => Some(el.start_token.byte_end - 1),
+            false => find_next_line_break_pos(content, bytes, el.start_token.byte_end, false),
+        };
+        let start_el_remove_end_pos = start_el_line_end
+            .and_then(|pos| find_next_line_break_pos(content, bytes, pos + 1, false));
         let end_el_remove_start_pos =
-            find_prev_line_break_pos(self.content.as_ref(), bytes, el.end_token.byte_start, false)
-                .and_then(|pos| find_prev_line_break_pos(self.content.as_ref(), bytes, pos, false));
+            find_prev_line_break_pos(content, bytes, el.end_token.byte_start, false)
+                .and_then(|pos| find_prev_line_break_pos(content, bytes, pos, false));
 
         // If the range is invalid, do nothing.
         match (start_el_remove_end_pos, end_el_remove_start_pos) {
             (Some(end), Some(start)) => {
                 if start > end {
                     (
-                        el.start_token.byte_start..end,
+                        el.start_token.byte_start..line_break_start(bytes, end),
                         Some(start + 1..el.end_token.byte_end),
                     )
                 } else {
@@ -36,6 +39,10 @@ impl MarkerBuilder for UnwrapBlockMarkerBuilder {
             _ => (el.start_token.byte_start..el.start_token.byte_start, None),
         }
     }
+
+    fn strategy_name(&self) -> &'static str {
+        "unwrap-block"
+    }
 }
 
 #[cfg(test)]
@@ -56,11 +63,9 @@ mod tests {
         #[case] expected_start_range: Range<usize>,
         #[case] expected_end_range: Option<Range<usize>>,
     ) {
-        let content = Rc::new(input.replace('+', "\n"));
+        let content = input.replace('+', "\n");
 
-        let builder = UnwrapBlockMarkerBuilder {
-            content: Rc::clone(&content),
-        };
+        let builder = UnwrapBlockMarkerBuilder::default();
 
         let tokens = tokenizer::tokenize(&content, "<", ">");
         let parsed = parser::parse(&tokens)
@@ -78,8 +83,53 @@ mod tests {
             .unwrap();
 
         assert_eq!(
-            builder.build(&parsed),
+            builder.build(&content, &parsed),
             (expected_start_range, expected_end_range)
         );
     }
+
+    #[test]
+    fn test_build_with_end_of_line_tag() {
+        // The line break of the start tag is contained in the token.
+        //                      10        20        30        40
+        //             01234567890123456789012345678901234567890123
+        //                 ^----------------^      ^-------------^
+        let content = "a+  // <r>+  if (a) {+    b+  }+  // </r>+c".replace('+', "\n");
+
+        let builder = UnwrapBlockMarkerBuilder::default();
+
+        let tokens = tokenizer::tokenize(&content, "// <", tokenizer::END_OF_LINE);
+        let parsed = parser::parse(&tokens)
+            .into_iter()
+            .find_map(|c| match c {
+                parser::ContentPart::Element(el) => Some(el),
+                _ => None,
+            })
+            .unwrap();
+
+        assert_eq!(builder.build(&content, &parsed), (4..21, Some(28..42)));
+    }
+
+    #[test]
+    fn test_build_with_crlf() {
+        // '+' is CRLF
+        //                      10        20        30
+        //             0123456789012345678901234567890123456789
+        //             |         ^----------^    ^-----------^
+        let content = "foo+bar+<remove>+{+b+}+</remove>+baz".replace('+', "\r\n");
+
+        let builder = UnwrapBlockMarkerBuilder::default();
+
+        let tokens = tokenizer::tokenize(&content, "<", ">");
+        let parsed = parser::parse(&tokens)
+            .into_iter()
+            .find_map(|c| match c {
+                parser::ContentPart::Element(el) => Some(el),
+                _ => None,
+            })
+            .unwrap();
+
+        // "\r\n" after "{" and before "}" are kept.
+        assert_eq!(builder.build(&content, &parsed), (10..21, Some(26..38)));
+    }
 }
@@ -0,0 +1,253 @@
+use super::MarkerBuilder;
+use crate::{
+    code::utils::line_break_pos_finder::{find_prev_line_break_pos, line_break_start},
+    parser::Element,
+};
+use std::ops::Range;
+
+/// Build the ranges to keep the else branch of an if/else block.
+///
+/// ```text
+///  /* <tag unwrap-else> */  -+
+///  if (flag) {               |  removed (start range)
+///    A                       |
+///  } else {                 -+
+///    B                          kept and dedented
+///  }                        -+  removed (end range)
+///  /* </tag> */             -+
+/// ```
+///
+/// The else line is the first `} else {` (or `else {` after `}`) at the top level of the block.
+/// Braces in string literals and comments are not counted.
+/// If it is not found, or the block is followed by other code (e.g. `} else if (x) {`), nothing is removed.
+#[derive(Default)]
+pub struct UnwrapElseMarkerBuilder {}
+
+impl MarkerBuilder for UnwrapElseMarkerBuilder {
+    fn build(&self, content: &str, el: &Element) -> (Range<usize>, Option<Range<usize>>) {
+        let bytes = content.as_bytes();
+        let closer_line_start =
+            find_prev_line_break_pos(content, bytes, el.end_token.byte_start, false)
+                .and_then(|pos| find_prev_line_break_pos(content, bytes, pos, false))
+                .map(|pos| pos + 1);
+        let else_line_end = closer_line_start
+            .and_then(|end| find_else_line_end(content, el.start_token.byte_end..end));
+
+        match (else_line_end, closer_line_start) {
+            (Some(else_line_end), Some(closer_line_start)) => (
+                el.start_token.byte_start..line_break_start(bytes, else_line_end),
+                Some(closer_line_start..el.end_token.byte_end),
+            ),
+            _ => (el.start_token.byte_start..el.start_token.byte_start, None),
+        }
+    }
+
+    fn strategy_name(&self) -> &'static str {
+        "unwrap-else"
+    }
+}
+
+/// Find the position of the line break at the end of the else line in the range.
+/// `None` if the block is followed by other code than the else line (e.g. `} else if (...) {`),
+/// since the branches between them would be removed.
+fn find_else_line_end(content: &str, range: Range<usize>) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut is_block_opened = false;
+    let mut state = ScanState::default();
+    let mut line_start = range.start;
+
+    for line in content[range].split_inclusive('\n') {
+        let line_end = line_start + line.len();
+        let masked = mask_strings_and_comments(line, &mut state);
+        let mut code = masked.trim();
+
+        if let Some(rest) = code.strip_prefix('}') {
+            depth = depth.saturating_sub(1);
+            code = rest.trim_start();
+        }
+        if is_block_opened && depth == 0 && !code.is_empty() {
+            return (is_else(code) && line.ends_with('\n')).then_some(line_end - 1);
+        }
+
+        for c in code.chars() {
+            match c {
+                '{' => depth += 1,
+                '}' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+        is_block_opened |= depth > 0;
+        line_start = line_end;
+    }
+
+    None
+}
+
+/// `else {`, but not `else if (...) {`
+fn is_else(code: &str) -> bool {
+    code.strip_prefix("else")
+        .is_some_and(|rest| rest.trim() == "{")
+}
+
+/// The state of the scan carried over to the next line
+#[derive(Default, Clone, Copy, PartialEq)]
+enum ScanState {
+    #[default]
+    Code,
+    BlockComment,
+    TemplateLiteral,
+}
+
+/// Replace string literals and comments in the line with spaces, so that braces in them (e.g. `"}"`) are not counted.
+/// A quote without the closing quote in the line (e.g. a lifetime of Rust) does not start a string literal.
+fn mask_strings_and_comments(line: &str, state: &mut ScanState) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let mut masked = String::with_capacity(line.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        // The number of characters to be masked from `i`
+        let len = match *state {
+            ScanState::BlockComment => match find_comment_end(&chars, i) {
+                Some(end) => {
+                    *state = ScanState::Code;
+                    end - i
+                }
+                None => chars.len() - i,
+            },
+            ScanState::TemplateLiteral => match find_quote(&chars, i, '`') {
+                Some(end) => {
+                    *state = ScanState::Code;
+                    end + 1 - i
+                }
+                None => chars.len() - i,
+            },
+            ScanState::Code => match (chars[i], chars.get(i + 1)) {
+                ('/', Some('/')) => chars.len() - i,
+                ('/', Some('*')) => {
+                    *state = ScanState::BlockComment;
+                    2
+                }
+                ('`', _) => {
+                    *state = ScanState::TemplateLiteral;
+                    1
+                }
+                (quote @ ('"' | '\''), _) => {
+                    find_quote(&chars, i + 1, quote).map_or(0, |end| end + 1 - i)
+                }
+                _ => 0,
+            },
+        };
+
+        if len == 0 {
+            masked.push(chars[i]);
+            i += 1;
+        } else {
+            masked.extend(chars[i..i + len].iter().map(|c| match c {
+                '\r' | '\n' => *c,
+                _ => ' ',
+            }));
+            i += len;
+        }
+    }
+
+    masked
+}
+
+/// The position after `*/`
+fn find_comment_end(chars: &[char], from: usize) -> Option<usize> {
+    chars[from..]
+        .windows(2)
+        .position(|w| w == ['*', '/'])
+        .map(|pos| from + pos + 2)
+}
+
+/// The position of the closing quote. Escaped quotes are skipped.
+fn find_quote(chars: &[char], from: usize, quote: char) -> Option<usize> {
+    let mut i = from;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 2,
+            c if c == quote => return Some(i),
+            _ => i += 1,
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parser, tokenizer};
+    use rstest::rstest;
+
+    #[rstest]
+    //             10        20        30        40        50
+    //     012345678901234567890123456789012345678901234567890123
+    //     |   ^-----------------------------^    ^----------^
+    #[case("foo+<remove>+if (a) {+  b+} else {+  c+}+</remove>+baz", 4..34, Some(39..50))]
+    // The else of the nested block is skipped, and `else {` after `}` is found.
+    #[case(
+        "foo+<remove>+if (a) {+  if (b) {+  } else {+  }+}+else {+  c+}+</remove>",
+        4..56,
+        Some(61..72)
+    )]
+    #[case("foo+<remove>+if (a) {+  b+} else if (c) {+  d+}+</remove>", 4..4, None)]
+    // The else-if branch is not removed with the final else.
+    #[case(
+        "foo+<remove>+if (a) {+  b+} else if (x) {+  y+} else {+  c+}+</remove>",
+        4..4,
+        None
+    )]
+    #[case("foo+<remove>+if (a) {+  b+}+else if (x) {+}+else {+  c+}+</remove>", 4..4, None)]
+    // Code between the block and the else line
+    #[case("foo+<remove>+if (a) {+  b+}+d();+if (x) {+} else {+  c+}+</remove>", 4..4, None)]
+    // Braces in string literals and comments are not counted.
+    //             10        20        30        40        50        60
+    //     0123456789012345678901234567890123456789012345678901234567890
+    //     |   ^----------------------------------^         ^----------^
+    #[case("foo+<remove>+if (a) {+  b(\"}\")+} else {+  c(\"{\")+}+</remove>+baz", 4..39, Some(49..60))]
+    //     |   ^------------------------------------------^    ^----------^
+    #[case("foo+<remove>+if (a) { // {+  b+} else { /* } */+  c+}+</remove>", 4..47, Some(52..63))]
+    #[case("foo+<remove>+if (a) {+  /* {+  } else {+  */+  b+} else {+  c+}+</remove>", 4..57, Some(62..73))]
+    #[case("foo+<remove>+if (a) {+  b+}+</remove>", 4..4, None)]
+    #[case("foo+<remove> if (a) { b } else { c } </remove>", 4..4, None)]
+    fn test_build(
+        #[case] input: &str,
+        #[case] expected_start_range: Range<usize>,
+        #[case] expected_end_range: Option<Range<usize>>,
+    ) {
+        let content = input.replace('+', "\n");
+
+        let builder = UnwrapElseMarkerBuilder::default();
+
+        let tokens = tokenizer::tokenize(&content, "<", ">");
+        let parsed = parser::parse(&tokens)
+            .into_iter()
+            .find_map(|c| match c {
+                parser::ContentPart::Element(el) => Some(el),
+                _ => None,
+            })
+            .unwrap();
+
+        assert_eq!(
+            builder.build(&content, &parsed),
+            (expected_start_range, expected_end_range)
+        );
+    }
+
+    #[rstest]
+    #[case("a(\"}\") {", "a(   ) {")]
+    #[case("a('\\'', \"\\\"}\") {", "a(    ,      ) {")]
+    #[case("x = `{` // }", "x =         ")]
+    #[case("a /* } */ {", "a         {")]
+    // A lifetime is not a string literal.
+    #[case("impl Foo for &'a T {", "impl Foo for &'a T {")]
+    fn test_mask_strings_and_comments(#[case] line: &str, #[case] expected: &str) {
+        assert_eq!(
+            mask_strings_and_comments(line, &mut ScanState::default()),
+            expected
+        );
+    }
+}
@@ -0,0 +1,219 @@
+use super::MarkerBuilder;
+use crate::{code::utils::line_break_pos_finder::line_break_start, parser::Element};
+use std::ops::Range;
+
+/// Build the ranges to unwrap a block defined by indentation (e.g. Python, YAML).
+///
+/// ```text
+///  # <tag unwrap-indent>  -+
+///  if flag:               -+  removed (start range)
+///      A                      kept and dedented by one level
+///      B                      kept and dedented by one level
+///  # </tag>               ---  removed (end range)
+/// ```
+///
+/// Only the header line (e.g. `if flag:`, `with ...:` or a YAML key) is removed,
+/// and the last line of the body is kept unlike `UnwrapBlockMarkerBuilder`.
+/// One level of indentation (a tab or N spaces) is measured from the difference between the header and the first line of the body,
+/// and exactly that is removed from each line of the body regardless of the indent of the tags.
+///
+/// If the body is not indented deeper than the header, or its lines are indented inconsistently, nothing is removed.
+#[derive(Default)]
+pub struct UnwrapIndentMarkerBuilder {}
+
+impl MarkerBuilder for UnwrapIndentMarkerBuilder {
+    fn build(&self, content: &str, el: &Element) -> (Range<usize>, Option<Range<usize>>) {
+        let bytes = content.as_bytes();
+        let block = match find_block(content, el) {
+            Some(block) => block,
+            None => return (el.start_token.byte_start..el.start_token.byte_start, None),
+        };
+
+        (
+            el.start_token.byte_start..line_break_start(bytes, block.header_end),
+            Some(block.end_start..el.end_token.byte_end),
+        )
+    }
+
+    fn strategy_name(&self) -> &'static str {
+        "unwrap-indent"
+    }
+
+    /// The body is dedented by `dedent_ranges` instead of relative to the column of the tag.
+    fn keeps_indent(&self) -> bool {
+        true
+    }
+
+    fn dedent_ranges(&self, content: &str, el: &Element) -> Vec<Range<usize>> {
+        find_block(content, el)
+            .map(|block| block.dedent_ranges)
+            .unwrap_or_default()
+    }
+}
+
+struct IndentBlock {
+    /// The position of the line break at the end of the header line
+    header_end: usize,
+    /// The start of the end tag (including the indent if the end tag is at the beginning of the line)
+    end_start: usize,
+    /// One level of indentation of each line of the body
+    dedent_ranges: Vec<Range<usize>>,
+}
+
+fn find_block(content: &str, el: &Element) -> Option<IndentBlock> {
+    let bytes = content.as_bytes();
+    let region = el.start_token.byte_end..el.end_token.byte_start;
+    let mut line_start = region.start;
+    let mut lines = content[region.clone()].split_inclusive('\n').map(|line| {
+        let start = line_start;
+        line_start += line.len();
+        (start, line)
+    });
+
+    // The rest of the line of the start tag (a tag closed at the end of the line has no rest)
+    if region.start > 0 && bytes[region.start - 1] != b'\n' {
+        let (_, rest) = lines.next()?;
+        if !rest.trim().is_empty() || !rest.ends_with('\n') {
+            return None;
+        }
+    }
+
+    let (header_start, header) = lines.find(|(_, line)| !line.trim().is_empty())?;
+    if !header.ends_with('\n') {
+        return None;
+    }
+    let header_indent = indent(header);
+
+    // One level of indentation is the indent of the first line of the body following the indent of the header.
+    let mut level: Option<&str> = None;
+    let mut end_start = region.end;
+    let mut dedent_ranges = vec![];
+    for (start, line) in lines {
+        // The indent of the end tag
+        if !line.ends_with('\n') && line.trim().is_empty() {
+            end_start = start;
+            break;
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let rest = indent(line).strip_prefix(header_indent)?;
+        let level = *level.get_or_insert(rest);
+        if level.is_empty() || !rest.starts_with(level) {
+            return None;
+        }
+
+        let level_start = start + header_indent.len();
+        dedent_ranges.push(level_start..level_start + level.len());
+    }
+
+    (!dedent_ranges.is_empty()).then_some(IndentBlock {
+        header_end: header_start + header.len() - 1,
+        end_start,
+        dedent_ranges,
+    })
+}
+
+fn indent(line: &str) -> &str {
+    &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parser, tokenizer};
+    use rstest::rstest;
+
+    type RemovableRanges = (Range<usize>, Option<Range<usize>>);
+
+    fn build(content: &str, delimiter_start: &str, delimiter_end: &str) -> RemovableRanges {
+        let tokens = tokenizer::tokenize(content, delimiter_start, delimiter_end);
+        let parsed = parser::parse(&tokens)
+            .into_iter()
+            .find_map(|c| match c {
+                parser::ContentPart::Element(el) => Some(el),
+                _ => None,
+            })
+            .unwrap();
+
+        UnwrapIndentMarkerBuilder::default().build(content, &parsed)
+    }
+
+    fn dedent_ranges(content: &str) -> Vec<Range<usize>> {
+        let tokens = tokenizer::tokenize(content, "#<", "\n");
+        let parsed = parser::parse(&tokens)
+            .into_iter()
+            .find_map(|c| match c {
+                parser::ContentPart::Element(el) => Some(el),
+                _ => None,
+            })
+            .unwrap();
+
+        UnwrapIndentMarkerBuilder::default().dedent_ranges(content, &parsed)
+    }
+
+    #[rstest]
+    //             10        20        30        40
+    //     0123456789012345678901234567890123456789012
+    //     ^---------^                ^-----^
+    #[case("#<r>+if a:+    b()+    c()+#</r>+d()", 0..10, Some(27..33))]
+    // Indented with a tab
+    #[case("\t#<r>+\tif a:+\t\tb()+\t#</r>+", 1..12, Some(19..26))]
+    // YAML nested keys with blank lines in the body
+    //       ^----------^                   ^-------^
+    #[case("  #<r>+  key:+    a: 1++    b: 2+  #</r>+", 2..13, Some(33..41))]
+    // The body is not indented.
+    #[case("#<r>+if a:+b()+#</r>+", 0..0, None)]
+    // A line of the body is less indented than the header.
+    #[case("  #<r>+  if a:+    b()+ c()+  #</r>+", 2..2, None)]
+    // No body
+    #[case("#<r>+if a:+#</r>+", 0..0, None)]
+    fn test_build_with_line_comment(
+        #[case] input: &str,
+        #[case] expected_start_range: Range<usize>,
+        #[case] expected_end_range: Option<Range<usize>>,
+    ) {
+        let content = input.replace('+', "\n");
+
+        assert_eq!(
+            build(&content, "#<", "\n"),
+            (expected_start_range, expected_end_range)
+        );
+    }
+
+    #[rstest]
+    //             10        20        30        40
+    //     0123456789012345678901234567890123456789012
+    //                        ^--^        ^--^
+    #[case("#<r>+    if a:+        b()+        c()+#</r>+", vec![19..23, 31..35])]
+    // The tag is more indented than the header.
+    //                    ^^    ^^
+    #[case("    #<r>+if a:+  b()+  c()+    #</r>+", vec![15..17, 21..23])]
+    // Indented with tabs, and a line of the body is indented deeper.
+    //                    ^       ^
+    #[case("#<r>+\tif a:+\t\tb()+\t\t\tc()+#</r>+", vec![13..14, 19..20])]
+    // Blank lines are left as they are.
+    #[case("#<r>+if a:+  b()+ +  c()+#</r>+", vec![11..13, 19..21])]
+    // The body is indented inconsistently.
+    #[case("#<r>+if a:+    b()+\tc()+#</r>+", vec![])]
+    #[case("#<r>+if a:+    b()+  c()+#</r>+", vec![])]
+    // The body is not indented.
+    #[case("#<r>+if a:+b()+#</r>+", vec![])]
+    fn test_dedent_ranges(#[case] input: &str, #[case] expected: Vec<Range<usize>>) {
+        let content = input.replace('+', "\n");
+
+        assert_eq!(dedent_ranges(&content), expected);
+    }
+
+    #[test]
+    fn test_build_with_block_comment() {
+        // '+' is CRLF
+        //                      10        20
+        //             0123456789012345678901234
+        //             ^--------^         ^---^
+        let content = "<r>+key:+  a: 1+</r>+".replace('+', "\r\n");
+
+        assert_eq!(build(&content, "<", ">"), (0..9, Some(19..23)));
+    }
+}
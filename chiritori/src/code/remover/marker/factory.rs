@@ -1,13 +1,157 @@
 use super::{availability::MarkerAvailability, builder::MarkerBuilder};
+use crate::code::utils::line_break_pos_finder::line_break_start;
 use crate::parser::Element;
+use crate::tokenizer::{Token, TokenKind, END_OF_LINE};
 use std::ops::Range;
 
 pub type RemoveStrategies = Vec<(Box<dyn MarkerAvailability>, Box<dyn MarkerBuilder>)>;
 pub type RemovableRange = (Range<usize>, Option<Range<usize>>);
 
-pub fn create(element: &Element, remove_strategy_map: &RemoveStrategies) -> Option<RemovableRange> {
+/// Build the removable range with the first available strategy.
+/// The name of the strategy is returned together.
+pub fn create(
+    content: &str,
+    element: &Element,
+    remove_strategy_map: &RemoveStrategies,
+) -> Option<(RemovableRange, &'static str)> {
     remove_strategy_map
         .iter()
         .find(|(availability, _)| availability.is_available(element))
-        .map(|(_, builder)| builder.build(element))
+        .map(|(_, builder)| {
+            let (range, pair_range) = builder.build(content, element);
+
+            let is_pair_start = pair_range.is_some();
+
+            (
+                (
+                    fit_to_comment_lines(content, element, range, is_pair_start),
+                    pair_range.map(|r| fit_to_comment_lines(content, element, r, false)),
+                ),
+                builder.strategy_name(),
+            )
+        })
+}
+
+/// A tag closed at the end of the line is a whole comment line.
+/// If the range starts with such a tag, the indent before the tag is also removed.
+/// If the range starts in the middle of a line (e.g. a trailing comment),
+/// the line break of the tag at the end of the range is kept so as not to join the lines.
+///
+/// The start of a pair (e.g. unwrap-block) keeps the indent, since the block between the pair is dedented
+/// relative to the column of the tag. The indent is removed by `IndentRemover` instead.
+/// An empty range (nothing to be removed) is not extended.
+///
+/// ```text
+///         ____ <- extended
+///  source: a+    # <tag>+b+    # </tag>+c
+///                ^--------------------^
+///
+///  source: a  # <tag>+b+# </tag>+c
+///             ^-----------------^  <- the last line break is kept
+///
+///  (unwrap-block)
+///  source: a+  # <tag>+  if a {+    b+  }+  # </tag>+c
+///              ^-------------^     ^-----------^
+///              ‾‾ <- the indent is kept for the dedent
+/// ```
+fn fit_to_comment_lines(
+    content: &str,
+    element: &Element,
+    range: Range<usize>,
+    is_pair_start: bool,
+) -> Range<usize> {
+    let is_line_comment_tag = |token: &Token| matches!(&token.kind, TokenKind::Element(el) if el.delimiter_end == END_OF_LINE);
+    let starts_with_tag = [element.start_token, element.end_token]
+        .iter()
+        .any(|token| token.byte_start == range.start && is_line_comment_tag(token));
+    let ends_with_line_break = [element.start_token, element.end_token]
+        .iter()
+        .any(|token| {
+            token.byte_end == range.end && is_line_comment_tag(token) && token.value.ends_with('\n')
+        });
+
+    let bytes = content.as_bytes();
+    let indent_len = content[..range.start]
+        .bytes()
+        .rev()
+        .take_while(|b| *b == b' ' || *b == b'\t')
+        .count();
+    let line_start = range.start - indent_len;
+    let is_line_start = line_start == 0 || bytes[line_start - 1] == b'\n';
+
+    match (is_line_start, starts_with_tag, ends_with_line_break) {
+        (true, true, _) if !is_pair_start && !range.is_empty() => line_start..range.end,
+        (false, _, true) => range.start..line_break_start(bytes, range.end - 1),
+        _ => range,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code::remover::marker::{
+        availability::range_marker_availability::RangeMarkerAvailability,
+        builder::{
+            range_marker_builder::RangeMarkerBuilder,
+            unwrap_block_marker_builder::UnwrapBlockMarkerBuilder,
+        },
+    };
+    use crate::{parser, tokenizer};
+    use rstest::rstest;
+
+    #[rstest]
+    //             10        20        30
+    //     012345678901234567890123456789012345
+    //     |  ^------------------^
+    #[case("a+  # <r>+b+  # </r>+c", 2..21)]
+    #[case("a+# <r>+b+# </r>+c", 2..17)]
+    #[case("a  # <r>+b+# </r>+c", 3..17)]
+    #[case("a+  # <r>+b+  # </r>", 2..20)]
+    fn test_create_with_end_of_line_tag(#[case] input: &str, #[case] expected: Range<usize>) {
+        let content = input.replace('+', "\n");
+        let tokens = tokenizer::tokenize(&content, "# <", END_OF_LINE);
+        let parsed = parser::parse(&tokens);
+        let element = parsed
+            .iter()
+            .find_map(|c| match c {
+                parser::ContentPart::Element(el) => Some(el),
+                _ => None,
+            })
+            .unwrap();
+        let strategies: RemoveStrategies = vec![(
+            Box::new(RangeMarkerAvailability::default()),
+            Box::new(RangeMarkerBuilder::default()),
+        )];
+
+        assert_eq!(
+            create(&content, element, &strategies),
+            Some(((expected, None), "range"))
+        );
+    }
+
+    #[test]
+    fn test_create_unwrap_block_with_end_of_line_tag() {
+        //                      10        20        30        40
+        //             01234567890123456789012345678901234567890123
+        //                 ^----------------^      ^-------------^
+        let content = "a+  // <r>+  if (a) {+    b+  }+  // </r>+c".replace('+', "\n");
+        let tokens = tokenizer::tokenize(&content, "// <", END_OF_LINE);
+        let parsed = parser::parse(&tokens);
+        let element = parsed
+            .iter()
+            .find_map(|c| match c {
+                parser::ContentPart::Element(el) => Some(el),
+                _ => None,
+            })
+            .unwrap();
+        let strategies: RemoveStrategies = vec![(
+            Box::new(RangeMarkerAvailability::default()),
+            Box::new(UnwrapBlockMarkerBuilder::default()),
+        )];
+
+        assert_eq!(
+            create(&content, element, &strategies),
+            Some(((4..21, Some(28..42)), "unwrap-block"))
+        );
+    }
 }
@@ -1,8 +1,67 @@
 pub mod marker_evaluator;
 pub mod time_limited_evaluator;
+pub mod time_released_evaluator;
 
+use crate::code::utils::datetime_parser::{parse_datetime, DateTimeParseError};
 use crate::element_parser::Element;
+use chrono::{DateTime, FixedOffset};
+use thiserror::Error;
 
 pub trait RemovalEvaluator {
-    fn is_removal(&self, start_el: &Element) -> bool;
+    /// Returns true if the element is ready to be removed.
+    /// An error is returned if the attributes of the element are missing or malformed.
+    fn is_removal(&self, start_el: &Element) -> Result<bool, EvaluationError>;
+
+    /// Returns the date time when the element expires, if the evaluator is based on time.
+    fn expires_at(&self, _start_el: &Element) -> Option<DateTime<FixedOffset>> {
+        None
+    }
+}
+
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum EvaluationError {
+    #[error("The attribute \"{0}\" is required")]
+    MissingAttribute(String),
+    #[error("The attribute \"{name}\" is invalid: {reason}")]
+    InvalidAttribute { name: String, reason: String },
+}
+
+impl EvaluationError {
+    /// The code used in diagnostics
+    pub fn code(&self) -> &'static str {
+        match self {
+            EvaluationError::MissingAttribute(_) => "missing-attribute",
+            EvaluationError::InvalidAttribute { .. } => "invalid-attribute-value",
+        }
+    }
+}
+
+/// Parse the date time of the attribute (e.g. `to`).
+/// It is interpreted in the time zone given by the `tz` attribute, or in the time offset if `tz` is omitted.
+pub fn parse_datetime_attribute(
+    el: &Element,
+    name: &str,
+    time_offset: &str,
+) -> Result<DateTime<FixedOffset>, EvaluationError> {
+    let value = find_attr_value(el, name)
+        .ok_or_else(|| EvaluationError::MissingAttribute(name.to_string()))?;
+    let tz = find_attr_value(el, "tz");
+
+    parse_datetime(value, time_offset, tz).map_err(|e| {
+        let name = match e {
+            DateTimeParseError::UnknownTimeZone(_) => "tz",
+            _ => name,
+        };
+        EvaluationError::InvalidAttribute {
+            name: name.to_string(),
+            reason: e.to_string(),
+        }
+    })
+}
+
+fn find_attr_value<'a>(el: &'a Element, name: &str) -> Option<&'a str> {
+    el.attrs
+        .iter()
+        .find(|a| a.name == name)
+        .and_then(|a| a.value.as_deref())
 }
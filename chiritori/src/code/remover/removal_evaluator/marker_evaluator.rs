@@ -1,4 +1,4 @@
-use super::RemovalEvaluator;
+use super::{EvaluationError, RemovalEvaluator};
 use crate::element_parser::Element;
 use std::collections::HashSet;
 
@@ -8,18 +8,15 @@ pub struct MarkerEvaluator {
 }
 
 impl RemovalEvaluator for MarkerEvaluator {
-    fn is_removal(&self, start_el: &Element) -> bool {
+    fn is_removal(&self, start_el: &Element) -> Result<bool, EvaluationError> {
         let name_attr_value = start_el
             .attrs
             .iter()
             .find(|a| a.name == "name")
-            .and_then(|attr| attr.value);
+            .and_then(|attr| attr.value.as_deref())
+            .ok_or_else(|| EvaluationError::MissingAttribute("name".to_string()))?;
 
-        if let Some(name_attr_value) = name_attr_value {
-            self.marker_removal_names.contains(name_attr_value)
-        } else {
-            false
-        }
+        Ok(self.marker_removal_names.contains(name_attr_value))
     }
 }
 
@@ -34,20 +31,35 @@ mod tests {
             name: "marker",
             attrs: vec![Attribute {
                 name: "name",
-                value: Some("feature1"),
+                value: Some("feature1".into()),
             }],
         };
         let feature2_el = Element {
             name: "marker",
             attrs: vec![Attribute {
                 name: "name",
-                value: Some("feature2"),
+                value: Some("feature2".into()),
             }],
         };
         let evaluator = MarkerEvaluator {
             marker_removal_names: HashSet::from([String::from("feature1")]),
         };
-        assert!(evaluator.is_removal(&feature1_el));
-        assert!(!evaluator.is_removal(&feature2_el));
+        assert_eq!(evaluator.is_removal(&feature1_el), Ok(true));
+        assert_eq!(evaluator.is_removal(&feature2_el), Ok(false));
+    }
+
+    #[test]
+    fn test_remove_marker_without_name() {
+        let el = Element {
+            name: "marker",
+            attrs: vec![],
+        };
+        let evaluator = MarkerEvaluator {
+            marker_removal_names: HashSet::from([String::from("feature1")]),
+        };
+        assert_eq!(
+            evaluator.is_removal(&el),
+            Err(EvaluationError::MissingAttribute("name".to_string()))
+        );
     }
 }
@@ -1,6 +1,6 @@
-use super::RemovalEvaluator;
+use super::{parse_datetime_attribute, EvaluationError, RemovalEvaluator};
 use crate::element_parser::Element;
-use chrono::{DateTime, Local};
+use chrono::{DateTime, FixedOffset, Local};
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct TimeLimitedEvaluator {
@@ -9,27 +9,14 @@ pub struct TimeLimitedEvaluator {
 }
 
 impl RemovalEvaluator for TimeLimitedEvaluator {
-    fn is_removal(&self, start_el: &Element) -> bool {
-        let expires_attr = start_el.attrs.iter().find(|a| a.name == "to");
+    fn is_removal(&self, start_el: &Element) -> Result<bool, EvaluationError> {
+        let expires = parse_datetime_attribute(start_el, "to", &self.time_offset)?;
 
-        if expires_attr.is_none() || expires_attr.unwrap().value.is_none() {
-            return false;
-        }
-
-        let mut expires_str = expires_attr.unwrap().value.unwrap().to_string();
-        expires_str.push(' ');
-        expires_str.push_str(self.time_offset.as_str());
-        let expires = DateTime::parse_from_str(&expires_str, "%Y-%m-%d %H:%M:%S %z");
-
-        if expires.is_err() {
-            return false;
-        }
-
-        if self.current_time < expires.unwrap() {
-            return false;
-        }
+        Ok(self.current_time >= expires)
+    }
 
-        true
+    fn expires_at(&self, start_el: &Element) -> Option<DateTime<FixedOffset>> {
+        parse_datetime_attribute(start_el, "to", &self.time_offset).ok()
     }
 }
 
@@ -37,6 +24,7 @@ impl RemovalEvaluator for TimeLimitedEvaluator {
 mod tests {
     use super::*;
     use crate::element_parser::Attribute;
+    use rstest::rstest;
 
     #[test]
     fn test_remove_marker_outdated() {
@@ -44,7 +32,7 @@ mod tests {
             name: "time-limited",
             attrs: vec![Attribute {
                 name: "to",
-                value: Some("2021-12-31 23:00:00"),
+                value: Some("2021-12-31 23:00:00".into()),
             }],
         };
         let evaluator = TimeLimitedEvaluator {
@@ -56,7 +44,46 @@ mod tests {
             .into(),
             time_offset: "+0000".to_string(),
         };
-        assert!(evaluator.is_removal(&start_el));
+        assert_eq!(evaluator.is_removal(&start_el), Ok(true));
+    }
+
+    #[rstest]
+    #[case("2021-12-31T23:00:00+00:00", None, true)]
+    #[case("2022-01-01T09:00:01+09:00", None, false)]
+    #[case("2021/12/31 23:59:59", None, true)]
+    #[case("2022-01-01", None, true)]
+    #[case("2022-01-02", None, false)]
+    #[case("2022-01-01 08:59:59", Some("Asia/Tokyo"), true)]
+    #[case("2022-01-01 09:00:01", Some("Asia/Tokyo"), false)]
+    fn test_remove_marker_with_formats(
+        #[case] to: &str,
+        #[case] tz: Option<&str>,
+        #[case] expected: bool,
+    ) {
+        let mut attrs = vec![Attribute {
+            name: "to",
+            value: Some(to.into()),
+        }];
+        if let Some(tz) = tz {
+            attrs.push(Attribute {
+                name: "tz",
+                value: Some(tz.into()),
+            });
+        }
+        let start_el = Element {
+            name: "time-limited",
+            attrs,
+        };
+        let evaluator = TimeLimitedEvaluator {
+            current_time: DateTime::parse_from_str(
+                "2022-01-01 00:00:00 +0000",
+                "%Y-%m-%d %H:%M:%S %z",
+            )
+            .unwrap()
+            .into(),
+            time_offset: "+0000".to_string(),
+        };
+        assert_eq!(evaluator.is_removal(&start_el), Ok(expected));
     }
 
     #[test]
@@ -65,7 +92,7 @@ mod tests {
             name: "time-limited",
             attrs: vec![Attribute {
                 name: "to",
-                value: Some("2023-12-31 23:59:59"),
+                value: Some("2023-12-31 23:59:59".into()),
             }],
         };
         let evaluator = TimeLimitedEvaluator {
@@ -77,6 +104,69 @@ mod tests {
             .into(),
             time_offset: "+0000".to_string(),
         };
-        assert!(!evaluator.is_removal(&start_el));
+        assert_eq!(evaluator.is_removal(&start_el), Ok(false));
+    }
+
+    #[rstest]
+    #[case(vec![], EvaluationError::MissingAttribute("to".to_string()))]
+    #[case(
+        vec![Attribute { name: "to", value: None }],
+        EvaluationError::MissingAttribute("to".to_string())
+    )]
+    #[case(
+        vec![Attribute { name: "to", value: Some("2022-13-01 00:00:00".into()) }],
+        EvaluationError::InvalidAttribute {
+            name: "to".to_string(),
+            reason: "Invalid date time: \"2022-13-01 00:00:00\"".to_string()
+        }
+    )]
+    #[case(
+        vec![
+            Attribute { name: "to", value: Some("2022-01-01 00:00:00".into()) },
+            Attribute { name: "tz", value: Some("Unknown/Zone".into()) }
+        ],
+        EvaluationError::InvalidAttribute {
+            name: "tz".to_string(),
+            reason: "Unknown time zone: \"Unknown/Zone\"".to_string()
+        }
+    )]
+    fn test_remove_marker_invalid(
+        #[case] attrs: Vec<Attribute>,
+        #[case] expected: EvaluationError,
+    ) {
+        let start_el = Element {
+            name: "time-limited",
+            attrs,
+        };
+        let evaluator = TimeLimitedEvaluator {
+            current_time: Local::now(),
+            time_offset: "+0000".to_string(),
+        };
+        assert_eq!(evaluator.is_removal(&start_el), Err(expected));
+    }
+
+    #[rstest]
+    #[case(Some("2022-01-01 00:00:00"), Some("2022-01-01T00:00:00+09:00"))]
+    #[case(Some("2022-13-01 00:00:00"), None)]
+    #[case(None, None)]
+    fn test_expires_at(#[case] to: Option<&str>, #[case] expected: Option<&str>) {
+        let start_el = Element {
+            name: "time-limited",
+            attrs: to
+                .map(|to| Attribute {
+                    name: "to",
+                    value: Some(to.into()),
+                })
+                .into_iter()
+                .collect(),
+        };
+        let evaluator = TimeLimitedEvaluator {
+            current_time: Local::now(),
+            time_offset: "+0900".to_string(),
+        };
+        assert_eq!(
+            evaluator.expires_at(&start_el),
+            expected.map(|v| DateTime::parse_from_rfc3339(v).unwrap())
+        );
     }
 }
@@ -0,0 +1,122 @@
+use super::{parse_datetime_attribute, EvaluationError, RemovalEvaluator};
+use crate::element_parser::Element;
+use chrono::{DateTime, FixedOffset, Local};
+
+/// Evaluates elements revealed after the release date (the `from` attribute).
+///
+/// ```text
+///                  from
+///  ----------------|------------------>
+///   keep / remove  |  strip the tags
+///   the block      |  (the content is revealed)
+/// ```
+///
+/// After the release date, the element is removed with a strategy that keeps the content (e.g. strip-tags).
+/// Before that, the whole block is removed only if `remove_before_release` is set (e.g. for production builds).
+#[derive(Debug, PartialEq, Clone)]
+pub struct TimeReleasedEvaluator {
+    pub current_time: DateTime<Local>,
+    pub time_offset: String,
+    pub remove_before_release: bool,
+}
+
+impl TimeReleasedEvaluator {
+    /// Returns true if the release date has come.
+    pub fn is_released(&self, start_el: &Element) -> Result<bool, EvaluationError> {
+        let released_at = parse_datetime_attribute(start_el, "from", &self.time_offset)?;
+
+        Ok(self.current_time >= released_at)
+    }
+}
+
+impl RemovalEvaluator for TimeReleasedEvaluator {
+    fn is_removal(&self, start_el: &Element) -> Result<bool, EvaluationError> {
+        Ok(self.is_released(start_el)? || self.remove_before_release)
+    }
+
+    /// The release date, when the tags are stripped
+    fn expires_at(&self, start_el: &Element) -> Option<DateTime<FixedOffset>> {
+        parse_datetime_attribute(start_el, "from", &self.time_offset).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::element_parser::Attribute;
+    use rstest::rstest;
+
+    fn create_evaluator(remove_before_release: bool) -> TimeReleasedEvaluator {
+        TimeReleasedEvaluator {
+            current_time: DateTime::parse_from_rfc3339("2022-01-01T00:00:00+00:00")
+                .unwrap()
+                .into(),
+            time_offset: "+0000".to_string(),
+            remove_before_release,
+        }
+    }
+
+    fn create_element(from: &str) -> Element<'_> {
+        Element {
+            name: "time-released",
+            attrs: vec![Attribute {
+                name: "from",
+                value: Some(from.into()),
+            }],
+        }
+    }
+
+    #[rstest]
+    #[case("2021-12-31 23:59:59", false, Ok(true), Ok(true))]
+    #[case("2022-01-01 00:00:00", false, Ok(true), Ok(true))]
+    #[case("2022-01-01 00:00:01", false, Ok(false), Ok(false))]
+    #[case("2022-01-01 00:00:01", true, Ok(false), Ok(true))]
+    #[case(
+        "2022-13-01",
+        false,
+        Err(EvaluationError::InvalidAttribute {
+            name: "from".to_string(),
+            reason: "Invalid date time: \"2022-13-01\"".to_string()
+        }),
+        Err(EvaluationError::InvalidAttribute {
+            name: "from".to_string(),
+            reason: "Invalid date time: \"2022-13-01\"".to_string()
+        })
+    )]
+    fn test_is_removal(
+        #[case] from: &str,
+        #[case] remove_before_release: bool,
+        #[case] expected_released: Result<bool, EvaluationError>,
+        #[case] expected_removal: Result<bool, EvaluationError>,
+    ) {
+        let evaluator = create_evaluator(remove_before_release);
+        let start_el = create_element(from);
+
+        assert_eq!(evaluator.is_released(&start_el), expected_released);
+        assert_eq!(evaluator.is_removal(&start_el), expected_removal);
+    }
+
+    #[test]
+    fn test_is_removal_without_from() {
+        let start_el = Element {
+            name: "time-released",
+            attrs: vec![Attribute {
+                name: "to",
+                value: Some("2022-01-01".into()),
+            }],
+        };
+
+        assert_eq!(
+            create_evaluator(true).is_removal(&start_el),
+            Err(EvaluationError::MissingAttribute("from".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_expires_at() {
+        assert_eq!(
+            create_evaluator(false).expires_at(&create_element("2022-02-01")),
+            Some(DateTime::parse_from_rfc3339("2022-02-01T00:00:00+00:00").unwrap())
+        );
+    }
+}
@@ -0,0 +1,232 @@
+use std::ops::Range;
+use tree_sitter::{Language, Node, Parser, Tree};
+
+/// Field names of the body of statements (e.g. `consequence` of `if`, `body` of `for`)
+const BODY_FIELD_NAMES: &[&str] = &["consequence", "body"];
+/// Kinds of bare blocks (e.g. `{ ... }` in JavaScript)
+const BLOCK_KINDS: &[&str] = &["statement_block", "block", "compound_statement"];
+
+/// Languages whose syntax trees are available, chosen by the name of the language preset.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SyntaxLanguage {
+    JavaScript,
+    TypeScript,
+    Python,
+    Go,
+    Rust,
+    Java,
+    Php,
+}
+
+impl SyntaxLanguage {
+    /// Find the language by the name of the language preset (e.g. `js`, `python`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "js" => Some(SyntaxLanguage::JavaScript),
+            "ts" => Some(SyntaxLanguage::TypeScript),
+            "python" => Some(SyntaxLanguage::Python),
+            "go" => Some(SyntaxLanguage::Go),
+            "rust" => Some(SyntaxLanguage::Rust),
+            "java" => Some(SyntaxLanguage::Java),
+            "php" => Some(SyntaxLanguage::Php),
+            _ => None,
+        }
+    }
+
+    fn grammar(&self) -> Language {
+        match self {
+            SyntaxLanguage::JavaScript => tree_sitter_javascript::LANGUAGE.into(),
+            // The TSX grammar also parses `.tsx` files sharing the `ts` preset.
+            SyntaxLanguage::TypeScript => tree_sitter_typescript::LANGUAGE_TSX.into(),
+            SyntaxLanguage::Python => tree_sitter_python::LANGUAGE.into(),
+            SyntaxLanguage::Go => tree_sitter_go::LANGUAGE.into(),
+            SyntaxLanguage::Rust => tree_sitter_rust::LANGUAGE.into(),
+            SyntaxLanguage::Java => tree_sitter_java::LANGUAGE.into(),
+            SyntaxLanguage::Php => tree_sitter_php::LANGUAGE_PHP.into(),
+        }
+    }
+
+    /// Parse the source code. `None` if the grammar cannot be loaded.
+    pub fn parse(&self, content: &str) -> Option<Tree> {
+        let mut parser = Parser::new();
+        parser.set_language(&self.grammar()).ok()?;
+
+        parser.parse(content, None)
+    }
+}
+
+/// A statement (or a bare block) wrapping a block.
+///
+/// ```text
+///   if (flag) {
+///   ^          ^
+///   |          body_start
+///   wrapper_start
+///     foo();
+///   } else {
+///   ^ body_end
+///     bar();
+///   }
+///    ^ wrapper_end
+/// ```
+///
+/// `body_start..body_end` is the inside of the braces (or the whole block for languages without braces).
+#[derive(Debug, PartialEq, Eq)]
+pub struct SyntaxBlock {
+    pub wrapper: Range<usize>,
+    pub body: Range<usize>,
+}
+
+/// Find the statement or the bare block occupying exactly the range (surrounding whitespace is ignored).
+/// `None` if the range contains other nodes than the one statement, or the statement has no body.
+pub fn find_block(tree: &Tree, content: &str, range: Range<usize>) -> Option<SyntaxBlock> {
+    let text = content.get(range.clone())?;
+    let start = range.start + (text.len() - text.trim_start().len());
+    let end = range.start + text.trim_end().len();
+    if start >= end {
+        return None;
+    }
+
+    // The comment of the end tag may be included in the node as an extra, so it is ignored.
+    let occupies = |node: &Node| node.start_byte() == start && end_without_extras(node) == end;
+    let mut node = tree
+        .root_node()
+        .descendant_for_byte_range(start, end)
+        .filter(occupies)?;
+
+    // Nodes with the same range (e.g. an expression statement wrapping `if` in Rust) are tried from the innermost.
+    loop {
+        if let Some(body) = find_body(&node) {
+            return Some(SyntaxBlock {
+                wrapper: start..end,
+                body: inner_range(&body),
+            });
+        }
+
+        node = node.parent().filter(occupies)?;
+    }
+}
+
+/// The end of the node without trailing extras (e.g. comments).
+fn end_without_extras(node: &Node) -> usize {
+    let mut cursor = node.walk();
+    let last = node
+        .children(&mut cursor)
+        .filter(|child| !child.is_extra())
+        .last();
+
+    match last {
+        Some(last) => end_without_extras(&last),
+        None => node.end_byte(),
+    }
+}
+
+fn find_body<'a>(node: &Node<'a>) -> Option<Node<'a>> {
+    if BLOCK_KINDS.contains(&node.kind()) {
+        return Some(*node);
+    }
+
+    BODY_FIELD_NAMES
+        .iter()
+        .find_map(|name| node.child_by_field_name(name))
+}
+
+/// The range of the block without the braces (e.g. `{` and `}`, or `:` and `endif;` of PHP).
+fn inner_range(block: &Node) -> Range<usize> {
+    let mut cursor = block.walk();
+    let children: Vec<_> = block
+        .children(&mut cursor)
+        .filter(|child| !child.is_extra())
+        .collect();
+
+    let start = match children.first() {
+        Some(first) if !first.is_named() => first.end_byte(),
+        _ => block.start_byte(),
+    };
+    let end = match children.last() {
+        Some(last) if !last.is_named() && children.len() > 1 => last.start_byte(),
+        _ => block.end_byte(),
+    };
+
+    start..end.max(start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(SyntaxLanguage::JavaScript, "if (a) {\n  b();\n}", "{\n  b();\n}")]
+    #[case(SyntaxLanguage::JavaScript, "{\n  b();\n}", "{\n  b();\n}")]
+    #[case(
+        SyntaxLanguage::JavaScript,
+        "if (a) { b(); } else { c(); }",
+        "{ b(); }"
+    )]
+    #[case(
+        SyntaxLanguage::TypeScript,
+        "if (a as boolean) {\n  b();\n}",
+        "{\n  b();\n}"
+    )]
+    #[case(SyntaxLanguage::Python, "if a:\n    b()\n    c()", "b()\n    c()")]
+    #[case(SyntaxLanguage::Go, "if a {\n\tb()\n}", "{\n\tb()\n}")]
+    #[case(SyntaxLanguage::Rust, "if a {\n    b();\n}", "{\n    b();\n}")]
+    #[case(SyntaxLanguage::Java, "while (a) {\n  b();\n}", "{\n  b();\n}")]
+    #[case(SyntaxLanguage::Php, "if ($a) {\n  b();\n}", "{\n  b();\n}")]
+    fn test_find_block(
+        #[case] language: SyntaxLanguage,
+        #[case] statement: &str,
+        #[case] expected_block: &str,
+    ) {
+        // The statement is placed in a function.
+        let (prefix, suffix) = match language {
+            SyntaxLanguage::JavaScript | SyntaxLanguage::TypeScript => {
+                ("function f() {\n", "\n}\n")
+            }
+            SyntaxLanguage::Python => ("", "\n"),
+            SyntaxLanguage::Go => ("package main\nfunc f() {\n", "\n}\n"),
+            SyntaxLanguage::Rust => ("fn f() {\n", "\n}\n"),
+            SyntaxLanguage::Java => ("class A { void f() {\n", "\n} }\n"),
+            SyntaxLanguage::Php => ("<?php\nfunction f() {\n", "\n}\n"),
+        };
+        let content = format!("{}{}{}", prefix, statement, suffix);
+        let tree = language.parse(&content).unwrap();
+
+        let range = prefix.len()..prefix.len() + statement.len();
+        let block = find_block(&tree, &content, range.clone()).unwrap();
+        let block_start = content[range.clone()].find(expected_block).unwrap() + range.start;
+        let expected_body = match language {
+            SyntaxLanguage::Python => block_start..block_start + expected_block.len(),
+            _ => block_start + 1..block_start + expected_block.len() - 1,
+        };
+
+        assert_eq!(
+            block,
+            SyntaxBlock {
+                wrapper: range,
+                body: expected_body
+            }
+        );
+    }
+
+    #[rstest]
+    // Several statements
+    #[case("a();\nif (b) {\n  c();\n}")]
+    // A statement without a body
+    #[case("a();")]
+    #[case("  ")]
+    fn test_find_block_not_found(#[case] statement: &str) {
+        let tree = SyntaxLanguage::JavaScript.parse(statement).unwrap();
+
+        assert_eq!(find_block(&tree, statement, 0..statement.len()), None);
+    }
+
+    #[rstest]
+    #[case("js", Some(SyntaxLanguage::JavaScript))]
+    #[case("php", Some(SyntaxLanguage::Php))]
+    #[case("html", None)]
+    fn test_from_name(#[case] name: &str, #[case] expected: Option<SyntaxLanguage>) {
+        assert_eq!(SyntaxLanguage::from_name(name), expected);
+    }
+}
@@ -1,4 +1,6 @@
 pub mod blank_counter;
 pub mod char_pos_finder;
+pub mod datetime_parser;
+pub mod edit_distance;
 pub mod line_break_pos_finder;
 pub mod line_map;
@@ -0,0 +1,431 @@
+use chrono::{
+    DateTime, Datelike, Days, FixedOffset, LocalResult, NaiveDate, NaiveDateTime, NaiveTime,
+    TimeDelta, TimeZone, Weekday,
+};
+use chrono_tz::Tz;
+use thiserror::Error;
+
+const DATETIME_FORMATS: [&str; 6] = [
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y/%m/%d %H:%M:%S%.f",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%dT%H:%M",
+    "%Y/%m/%d %H:%M",
+];
+const DATE_FORMATS: [&str; 2] = ["%Y-%m-%d", "%Y/%m/%d"];
+
+#[derive(Error, Debug, PartialEq)]
+pub enum DateTimeParseError {
+    #[error("Invalid date time: \"{0}\"")]
+    InvalidDateTime(String),
+    #[error("Invalid time offset: \"{0}\"")]
+    InvalidTimeOffset(String),
+    #[error("Unknown time zone: \"{0}\"")]
+    UnknownTimeZone(String),
+    #[error("Invalid current time: \"{0}\" (expected a date time like \"2025-03-01 12:00\", a month like \"2025-03\", or a relative expression like \"+7d\", \"tomorrow\" or \"next monday\")")]
+    InvalidCurrentTime(String),
+    #[error("Invalid Unix timestamp: \"{0}\" (expected seconds since 1970-01-01 00:00:00 UTC)")]
+    InvalidUnixTimestamp(String),
+}
+
+/// Parse a date time written in a tag.
+///
+/// The following formats are accepted.
+///
+/// | Format                              | Example                     |
+/// | ---                                 | ---                         |
+/// | RFC 3339 (with an embedded offset)  | 2024-02-15T12:00:00+09:00   |
+/// | Date time                           | 2024-02-15 12:00:00         |
+/// | Date time (slash separated)         | 2024/02/15 12:00:00         |
+/// | Date (the beginning of the day)     | 2024-02-15, 2024/02/15      |
+///
+/// A date time without an offset is interpreted in the time zone `tz` (IANA name such as "Asia/Tokyo")
+/// if it is given, otherwise in the fixed `time_offset` (such as "+09:00").
+pub fn parse_datetime(
+    value: &str,
+    time_offset: &str,
+    tz: Option<&str>,
+) -> Result<DateTime<FixedOffset>, DateTimeParseError> {
+    let value = value.trim();
+
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Ok(datetime);
+    }
+
+    let naive = parse_naive_datetime(value)
+        .ok_or_else(|| DateTimeParseError::InvalidDateTime(value.to_string()))?;
+
+    match tz {
+        Some(tz) => {
+            let tz: Tz = tz
+                .parse()
+                .map_err(|_| DateTimeParseError::UnknownTimeZone(tz.to_string()))?;
+
+            localize(&tz, naive)
+                .map(|datetime| datetime.fixed_offset())
+                .ok_or_else(|| DateTimeParseError::InvalidDateTime(value.to_string()))
+        }
+        None => {
+            let offset = parse_time_offset(time_offset)?;
+
+            localize(&offset, naive)
+                .ok_or_else(|| DateTimeParseError::InvalidDateTime(value.to_string()))
+        }
+    }
+}
+
+/// Parse the current time used to evaluate tags, to preview the source code at another time.
+///
+/// In addition to the formats of [`parse_datetime`], the following expressions are accepted.
+///
+/// | Format                                   | Example                           |
+/// | ---                                      | ---                               |
+/// | Month (the beginning of the month)       | 2025-03, 2025/03                  |
+/// | Relative time (`s`, `m`, `h`, `d`, `w`)  | +7d, -12h, +1d12h                 |
+/// | Keyword (the beginning of the day)       | today, tomorrow, yesterday        |
+/// | Weekday (the beginning of the day)       | next monday, last fri             |
+/// | Keyword                                  | now                               |
+///
+/// Relative expressions are based on `now`. Days are counted in the fixed `time_offset`,
+/// which is also used for a date time without an offset.
+pub fn parse_current_datetime(
+    value: &str,
+    now: DateTime<FixedOffset>,
+    time_offset: &str,
+) -> Result<DateTime<FixedOffset>, DateTimeParseError> {
+    let offset = parse_time_offset(time_offset)?;
+    let expression = value.trim().to_ascii_lowercase();
+    let invalid = || DateTimeParseError::InvalidCurrentTime(value.to_string());
+
+    if expression == "now" {
+        return Ok(now);
+    }
+    if let Some(delta) = parse_relative_time(&expression) {
+        return now.checked_add_signed(delta).ok_or_else(invalid);
+    }
+
+    let today = now.with_timezone(&offset).date_naive();
+    let date = match expression.split_whitespace().collect::<Vec<_>>()[..] {
+        ["today"] => Some(today),
+        ["tomorrow"] => today.checked_add_days(Days::new(1)),
+        ["yesterday"] => today.checked_sub_days(Days::new(1)),
+        [direction @ ("next" | "last"), weekday] => {
+            let weekday = weekday.parse::<Weekday>().map_err(|_| invalid())?;
+            let days = Days::new(days_between(today.weekday(), weekday, direction == "next"));
+
+            match direction {
+                "next" => today.checked_add_days(days),
+                _ => today.checked_sub_days(days),
+            }
+        }
+        _ => parse_month(&expression),
+    };
+
+    match date {
+        Some(date) => localize(&offset, date.and_time(NaiveTime::MIN)).ok_or_else(invalid),
+        None => parse_datetime(value, time_offset, None)
+            // e.g. "2025-03-01 12:00:00+09:00"
+            .or_else(|_| value.trim().parse::<DateTime<FixedOffset>>())
+            .map_err(|_| invalid()),
+    }
+}
+
+/// Parse a Unix timestamp in seconds (e.g. the value of `SOURCE_DATE_EPOCH`).
+pub fn parse_unix_timestamp(value: &str) -> Result<DateTime<FixedOffset>, DateTimeParseError> {
+    value
+        .trim()
+        .parse::<i64>()
+        .ok()
+        .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+        .map(|datetime| datetime.fixed_offset())
+        .ok_or_else(|| DateTimeParseError::InvalidUnixTimestamp(value.to_string()))
+}
+
+/// Parse a relative time such as "+7d", "-12h" or "+1d12h".
+fn parse_relative_time(expression: &str) -> Option<TimeDelta> {
+    let (sign, mut rest) = match expression.split_at_checked(1)? {
+        ("+", rest) => (1, rest),
+        ("-", rest) => (-1, rest),
+        _ => return None,
+    };
+    if rest.is_empty() {
+        return None;
+    }
+
+    let mut delta = TimeDelta::zero();
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        let amount = rest[..digits].parse::<i64>().ok()?;
+        let unit = rest[digits..].chars().next()?;
+        rest = &rest[digits + unit.len_utf8()..];
+
+        let part = match unit {
+            's' => TimeDelta::try_seconds(amount),
+            'm' => TimeDelta::try_minutes(amount),
+            'h' => TimeDelta::try_hours(amount),
+            'd' => TimeDelta::try_days(amount),
+            'w' => TimeDelta::try_weeks(amount),
+            _ => None,
+        }?;
+        delta = delta.checked_add(&part)?;
+    }
+
+    Some(delta * sign)
+}
+
+/// Parse a month such as "2025-03" or "2025/03" into its first day.
+fn parse_month(expression: &str) -> Option<NaiveDate> {
+    let (year, month) = expression.split_once(['-', '/'])?;
+    if year.len() != 4 || !(1..=2).contains(&month.len()) {
+        return None;
+    }
+    if !year
+        .chars()
+        .chain(month.chars())
+        .all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+
+    NaiveDate::from_ymd_opt(year.parse().ok()?, month.parse().ok()?, 1)
+}
+
+/// The number of days to the next (or from the last) weekday, excluding the day itself.
+fn days_between(from: Weekday, to: Weekday, forward: bool) -> u64 {
+    let days = match forward {
+        true => to.days_since(from),
+        false => from.days_since(to),
+    };
+
+    match days {
+        0 => 7,
+        days => days as u64,
+    }
+}
+
+/// Parse a time offset such as "+09:00" or "+0900".
+pub fn parse_time_offset(time_offset: &str) -> Result<FixedOffset, DateTimeParseError> {
+    DateTime::parse_from_str(
+        &format!("2000-01-01 00:00:00 {}", time_offset.trim()),
+        "%Y-%m-%d %H:%M:%S %z",
+    )
+    .map(|datetime| *datetime.offset())
+    .map_err(|_| DateTimeParseError::InvalidTimeOffset(time_offset.to_string()))
+}
+
+fn parse_naive_datetime(value: &str) -> Option<NaiveDateTime> {
+    DATETIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| {
+            DATE_FORMATS
+                .iter()
+                .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+                .map(|date| date.and_time(NaiveTime::MIN))
+        })
+}
+
+/// Convert the local date time to the date time in the time zone.
+///
+/// An ambiguous local time (e.g. at the end of DST) is resolved to the earlier one,
+/// and a local time skipped by DST is shifted forward by the length of the gap (one hour).
+fn localize<T: TimeZone>(tz: &T, naive: NaiveDateTime) -> Option<DateTime<FixedOffset>> {
+    match tz.from_local_datetime(&naive) {
+        LocalResult::Single(datetime) | LocalResult::Ambiguous(datetime, _) => {
+            Some(datetime.fixed_offset())
+        }
+        LocalResult::None => tz
+            .from_local_datetime(&(naive + TimeDelta::hours(1)))
+            .earliest()
+            .map(|datetime| datetime.fixed_offset()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("2024-02-15 12:00:00", "+00:00", None, "2024-02-15T12:00:00+00:00")]
+    #[case("2024-02-15 12:00:00", "+0900", None, "2024-02-15T12:00:00+09:00")]
+    #[case("2024/02/15 12:00:00", "+00:00", None, "2024-02-15T12:00:00+00:00")]
+    #[case("2024-02-15T12:00:00", "+00:00", None, "2024-02-15T12:00:00+00:00")]
+    #[case("2024-02-15 12:00", "+00:00", None, "2024-02-15T12:00:00+00:00")]
+    #[case("2024-02-15 12:00:00.5", "+00:00", None, "2024-02-15T12:00:00.5+00:00")]
+    #[case("2024-02-15", "+09:00", None, "2024-02-15T00:00:00+09:00")]
+    #[case("2024/02/15", "+00:00", None, "2024-02-15T00:00:00+00:00")]
+    #[case(
+        "2024-02-15T12:00:00+09:00",
+        "+00:00",
+        None,
+        "2024-02-15T12:00:00+09:00"
+    )]
+    #[case("2024-02-15T12:00:00Z", "+09:00", None, "2024-02-15T12:00:00+00:00")]
+    #[case(
+        "2024-02-15T12:00:00-05:00",
+        "+00:00",
+        Some("Asia/Tokyo"),
+        "2024-02-15T12:00:00-05:00"
+    )]
+    #[case(
+        "2024-02-15 12:00:00",
+        "+00:00",
+        Some("Asia/Tokyo"),
+        "2024-02-15T12:00:00+09:00"
+    )]
+    // DST in New York
+    #[case(
+        "2024-01-15 12:00:00",
+        "+00:00",
+        Some("America/New_York"),
+        "2024-01-15T12:00:00-05:00"
+    )]
+    #[case(
+        "2024-07-15 12:00:00",
+        "+00:00",
+        Some("America/New_York"),
+        "2024-07-15T12:00:00-04:00"
+    )]
+    // Ambiguous (DST ends)
+    #[case(
+        "2024-11-03 01:30:00",
+        "+00:00",
+        Some("America/New_York"),
+        "2024-11-03T01:30:00-04:00"
+    )]
+    // Skipped (DST starts)
+    #[case(
+        "2024-03-10 02:30:00",
+        "+00:00",
+        Some("America/New_York"),
+        "2024-03-10T03:30:00-04:00"
+    )]
+    fn test_parse_datetime(
+        #[case] value: &str,
+        #[case] time_offset: &str,
+        #[case] tz: Option<&str>,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(
+            parse_datetime(value, time_offset, tz),
+            Ok(DateTime::parse_from_rfc3339(expected).unwrap())
+        );
+    }
+
+    #[rstest]
+    #[case(
+        "2024-02-30 12:00:00",
+        "+00:00",
+        None,
+        DateTimeParseError::InvalidDateTime("2024-02-30 12:00:00".to_string())
+    )]
+    #[case(
+        "tomorrow",
+        "+00:00",
+        None,
+        DateTimeParseError::InvalidDateTime("tomorrow".to_string())
+    )]
+    #[case(
+        "2024-02-15 12:00:00",
+        "9:00",
+        None,
+        DateTimeParseError::InvalidTimeOffset("9:00".to_string())
+    )]
+    #[case(
+        "2024-02-15 12:00:00",
+        "+00:00",
+        Some("Asia/Tokio"),
+        DateTimeParseError::UnknownTimeZone("Asia/Tokio".to_string())
+    )]
+    fn test_parse_datetime_invalid(
+        #[case] value: &str,
+        #[case] time_offset: &str,
+        #[case] tz: Option<&str>,
+        #[case] expected: DateTimeParseError,
+    ) {
+        assert_eq!(parse_datetime(value, time_offset, tz), Err(expected));
+    }
+
+    // 2025-02-26 (Wed) 20:00:00 in UTC is 2025-02-27 (Thu) 05:00:00 in +09:00
+    const NOW: &str = "2025-02-26T20:00:00+00:00";
+
+    #[rstest]
+    #[case("now", "+00:00", "2025-02-26T20:00:00+00:00")]
+    #[case("+7d", "+00:00", "2025-03-05T20:00:00+00:00")]
+    #[case("-12h", "+00:00", "2025-02-26T08:00:00+00:00")]
+    #[case("+1d12h30m", "+00:00", "2025-02-28T08:30:00+00:00")]
+    #[case("+2w", "+00:00", "2025-03-12T20:00:00+00:00")]
+    #[case("+90s", "+00:00", "2025-02-26T20:01:30+00:00")]
+    #[case("today", "+00:00", "2025-02-26T00:00:00+00:00")]
+    #[case("today", "+09:00", "2025-02-27T00:00:00+09:00")]
+    #[case("Tomorrow", "+00:00", "2025-02-27T00:00:00+00:00")]
+    #[case("yesterday", "+00:00", "2025-02-25T00:00:00+00:00")]
+    #[case("next monday", "+00:00", "2025-03-03T00:00:00+00:00")]
+    #[case("next wed", "+00:00", "2025-03-05T00:00:00+00:00")]
+    #[case("next thursday", "+09:00", "2025-03-06T00:00:00+09:00")]
+    #[case("last  friday", "+00:00", "2025-02-21T00:00:00+00:00")]
+    #[case("last wednesday", "+00:00", "2025-02-19T00:00:00+00:00")]
+    #[case("2025-03", "+00:00", "2025-03-01T00:00:00+00:00")]
+    #[case("2025/3", "+09:00", "2025-03-01T00:00:00+09:00")]
+    #[case("2025-04-01 12:00", "+09:00", "2025-04-01T12:00:00+09:00")]
+    #[case("2025-04-01T12:00:00Z", "+09:00", "2025-04-01T12:00:00+00:00")]
+    #[case("2025-04-01 12:00:00-05:00", "+09:00", "2025-04-01T12:00:00-05:00")]
+    fn test_parse_current_datetime(
+        #[case] value: &str,
+        #[case] time_offset: &str,
+        #[case] expected: &str,
+    ) {
+        let now = DateTime::parse_from_rfc3339(NOW).unwrap();
+
+        assert_eq!(
+            parse_current_datetime(value, now, time_offset),
+            Ok(DateTime::parse_from_rfc3339(expected).unwrap())
+        );
+    }
+
+    #[rstest]
+    #[case("", "+00:00")]
+    #[case("+", "+00:00")]
+    #[case("+7", "+00:00")]
+    #[case("7d", "+00:00")]
+    #[case("+7y", "+00:00")]
+    #[case("next month", "+00:00")]
+    #[case("2025-13", "+00:00")]
+    #[case("2025-03-", "+00:00")]
+    #[case("next week", "+00:00")]
+    #[case("2025-02-30", "+00:00")]
+    fn test_parse_current_datetime_invalid(#[case] value: &str, #[case] time_offset: &str) {
+        let now = DateTime::parse_from_rfc3339(NOW).unwrap();
+
+        assert_eq!(
+            parse_current_datetime(value, now, time_offset),
+            Err(DateTimeParseError::InvalidCurrentTime(value.to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_current_datetime_invalid_time_offset() {
+        let now = DateTime::parse_from_rfc3339(NOW).unwrap();
+
+        assert_eq!(
+            parse_current_datetime("+7d", now, "9:00"),
+            Err(DateTimeParseError::InvalidTimeOffset("9:00".to_string()))
+        );
+    }
+
+    #[rstest]
+    #[case("1740600000", Ok("2025-02-26T20:00:00+00:00"))]
+    #[case(" 0\n", Ok("1970-01-01T00:00:00+00:00"))]
+    #[case("1740600000.5", Err(()))]
+    #[case("yesterday", Err(()))]
+    fn test_parse_unix_timestamp(#[case] value: &str, #[case] expected: Result<&str, ()>) {
+        assert_eq!(
+            parse_unix_timestamp(value),
+            expected
+                .map(|expected| DateTime::parse_from_rfc3339(expected).unwrap())
+                .map_err(|_| DateTimeParseError::InvalidUnixTimestamp(value.to_string()))
+        );
+    }
+}
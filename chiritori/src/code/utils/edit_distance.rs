@@ -0,0 +1,36 @@
+/// The Levenshtein distance between two strings (counted in characters).
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev_row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut row = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev_row[j] + if ca == *cb { 0 } else { 1 };
+            row[j + 1] = substitution.min(prev_row[j + 1] + 1).min(row[j] + 1);
+        }
+        prev_row = row;
+    }
+
+    prev_row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("", "", 0)]
+    #[case("abc", "", 3)]
+    #[case("", "abc", 3)]
+    #[case("time-limited", "time-limited", 0)]
+    #[case("time-limted", "time-limited", 1)]
+    #[case("time_limited", "time-limited", 1)]
+    #[case("tiem-limited", "time-limited", 2)]
+    #[case("kitten", "sitting", 3)]
+    #[case("あいう", "あう", 1)]
+    fn test_levenshtein(#[case] a: &str, #[case] b: &str, #[case] expected: usize) {
+        assert_eq!(levenshtein(a, b), expected);
+    }
+}
@@ -56,6 +56,25 @@ pub fn find_prev_line_break_pos(
     }
 }
 
+/// Returns the length of the line break ("\n" or "\r\n") starting at the byte position.
+pub fn line_break_len_at(bytes: &[u8], byte_pos: usize) -> Option<usize> {
+    match (bytes.get(byte_pos), bytes.get(byte_pos + 1)) {
+        (Some(b'\n'), _) => Some(1),
+        (Some(b'\r'), Some(b'\n')) => Some(2),
+        _ => None,
+    }
+}
+
+/// Returns the start position of the line break whose "\n" is at the byte position.
+/// It is the position of "\r" for CRLF.
+pub fn line_break_start(bytes: &[u8], lf_pos: usize) -> usize {
+    if lf_pos > 0 && bytes.get(lf_pos - 1) == Some(&b'\r') {
+        lf_pos - 1
+    } else {
+        lf_pos
+    }
+}
+
 #[derive(Debug)]
 enum CheckResult {
     Skip,
@@ -70,6 +89,8 @@ fn check(content: &str, bytes: &[u8], cursor: &usize) -> CheckResult {
     match bytes.get(*cursor) {
         Some(b' ') => CheckResult::Skip,
         Some(b'\t') => CheckResult::Skip,
+        // "\r" of CRLF is a part of the line break, and "\n" is reported as the line break.
+        Some(b'\r') if bytes.get(*cursor + 1) == Some(&b'\n') => CheckResult::Skip,
         Some(b'\n') => CheckResult::Found,
         None => CheckResult::None,
         _ => CheckResult::None,
@@ -149,4 +170,50 @@ mod tests {
             expected
         );
     }
+
+    #[rstest]
+    // '+' is CRLF
+    //
+    // byte_pos: 0   1   2   3   4   5   6   7   8   9
+    // content : f   o   o   \r  \n  \r  \n  _   _   b
+    #[case("foo++  bar", 3, true, Some(4))]
+    #[case("foo++  bar", 8, false, Some(6))]
+    #[case("foo++  bar", 6, false, Some(4))]
+    fn test_find_line_break_pos_with_crlf(
+        #[case] input: &str,
+        #[case] pos: usize,
+        #[case] forward: bool,
+        #[case] expected: Option<usize>,
+    ) {
+        let content = input.replace('+', "\r\n");
+        let bytes = content.as_bytes();
+        let found = if forward {
+            find_next_line_break_pos(&content, bytes, pos, true)
+        } else {
+            find_prev_line_break_pos(&content, bytes, pos, true)
+        };
+        assert_eq!(found, expected);
+    }
+
+    #[rstest]
+    #[case("a\nb", 1, Some(1))]
+    #[case("a\r\nb", 1, Some(2))]
+    #[case("a\rb", 1, None)]
+    #[case("ab", 1, None)]
+    #[case("a", 1, None)]
+    fn test_line_break_len_at(
+        #[case] content: &str,
+        #[case] pos: usize,
+        #[case] expected: Option<usize>,
+    ) {
+        assert_eq!(line_break_len_at(content.as_bytes(), pos), expected);
+    }
+
+    #[rstest]
+    #[case("a\nb", 1, 1)]
+    #[case("a\r\nb", 2, 1)]
+    #[case("\n", 0, 0)]
+    fn test_line_break_start(#[case] content: &str, #[case] pos: usize, #[case] expected: usize) {
+        assert_eq!(line_break_start(content.as_bytes(), pos), expected);
+    }
 }
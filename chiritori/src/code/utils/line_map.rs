@@ -20,6 +20,15 @@ pub fn find_line(line_map: &[usize], needle: usize) -> usize {
     }
 }
 
+/// Find the line and the column (1-based, counted in characters) of the byte position.
+pub fn find_line_column(content: &str, line_map: &[usize], byte_pos: usize) -> (usize, usize) {
+    let line = line_map.partition_point(|v| *v < byte_pos) + 1;
+    let line_start = if line > 1 { line_map[line - 2] + 1 } else { 0 };
+    let column = content[line_start..byte_pos].chars().count() + 1;
+
+    (line, column)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -43,4 +52,18 @@ mod tests {
         let mapped = build_line_map(&CONTENT.replace('+', "\n"));
         assert_eq!(find_line(&mapped, pos), expected);
     }
+
+    #[rstest]
+    #[case(0, (1, 1))]
+    #[case(2, (1, 3))]
+    #[case(3, (1, 4))]
+    #[case(4, (2, 1))]
+    #[case(9, (3, 2))]
+    #[case(21, (5, 2))]
+    #[case(24, (5, 5))]
+    fn test_find_line_column(#[case] pos: usize, #[case] expected: (usize, usize)) {
+        let content = "abc+def+efg+hijkl+あnopq".replace('+', "\n");
+        let mapped = build_line_map(&content);
+        assert_eq!(find_line_column(&content, &mapped, pos), expected);
+    }
 }
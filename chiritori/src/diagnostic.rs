@@ -0,0 +1,334 @@
+use crate::code::utils::line_map::find_line_column;
+use serde::Serialize;
+use std::fmt::Write;
+use std::ops::Range;
+use thiserror::Error;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Note => write!(f, "note"),
+        }
+    }
+}
+
+/// The location of a diagnostic in the source code.
+///
+/// ```text
+///   line:column           end_line:end_column
+///        v                    v
+/// foo <!-- <marker> --> bar
+///     ^^^^^^^^^^^^^^^^^
+///     byte_start..byte_end
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+pub struct Span {
+    /// The range in bytes (end exclusive)
+    pub byte_start: usize,
+    pub byte_end: usize,
+    /// 1-based line number of the start
+    pub line: usize,
+    /// 1-based column number of the start (counted in characters)
+    pub column: usize,
+    /// 1-based line number of the end
+    pub end_line: usize,
+    /// 1-based column number of the end (counted in characters, exclusive)
+    pub end_column: usize,
+}
+
+impl Span {
+    pub fn new(content: &str, line_map: &[usize], byte_range: Range<usize>) -> Self {
+        let (line, column) = find_line_column(content, line_map, byte_range.start);
+        let (end_line, end_column) = find_line_column(content, line_map, byte_range.end);
+
+        Self {
+            byte_start: byte_range.start,
+            byte_end: byte_range.end,
+            line,
+            column,
+            end_line,
+            end_column,
+        }
+    }
+
+    pub fn byte_range(&self) -> Range<usize> {
+        self.byte_start..self.byte_end
+    }
+}
+
+/// A problem found in the source code (or in the input), reported with its location.
+#[derive(Error, Debug, PartialEq, Clone, Serialize)]
+#[error("{severity}[{code}]: {message}")]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// A stable identifier of the kind of the problem (e.g. `unclosed-tag`)
+    pub code: &'static str,
+    pub message: String,
+    pub file: Option<String>,
+    /// `None` if the problem is not related to a position (e.g. the file cannot be read)
+    pub span: Option<Span>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            code,
+            message: message.into(),
+            file: None,
+            span: None,
+        }
+    }
+
+    pub fn error(code: &'static str, message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, code, message)
+    }
+
+    pub fn warning(code: &'static str, message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, code, message)
+    }
+
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    pub fn with_file(mut self, file: impl Into<String>) -> Self {
+        self.file = Some(file.into());
+        self
+    }
+
+    /// Render the diagnostic with the source line and caret underlines like rustc.
+    /// `content` is the source code the span points to.
+    ///
+    /// ```text
+    /// error[unclosed-tag]: The tag <marker> is not closed
+    ///  --> src/index.html:3:5
+    ///   |
+    /// 3 |     <!-- <marker> -->
+    ///   |     ^^^^^^^^^^^^^^^^^
+    /// ```
+    ///
+    /// Only the first line of a span across lines is shown.
+    pub fn render(&self, content: &str) -> String {
+        let mut output = self.to_string();
+
+        let Some(span) = &self.span else {
+            if let Some(file) = &self.file {
+                let _ = write!(output, "\n --> {}", file);
+            }
+            return output;
+        };
+
+        let gutter = " ".repeat(span.line.to_string().len());
+        let _ = write!(output, "\n{}--> {}", gutter, self.location());
+
+        let line_start = content[..span.byte_start]
+            .rfind('\n')
+            .map_or(0, |pos| pos + 1);
+        let line_end = content[span.byte_start..]
+            .find('\n')
+            .map_or(content.len(), |pos| span.byte_start + pos);
+        let line = content[line_start..line_end].trim_end_matches('\r');
+
+        // Tabs are kept so that the carets are aligned with the source line.
+        let padding: String = content[line_start..span.byte_start]
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let underline_end = span
+            .byte_end
+            .clamp(span.byte_start, line_start + line.len());
+        let underline = content[span.byte_start..underline_end]
+            .chars()
+            .count()
+            .max(1);
+
+        let source_line = format!("{} | {}", span.line, line);
+        let _ = write!(
+            output,
+            "\n{gutter} |\n{}\n{gutter} | {}{}",
+            source_line.trim_end(),
+            padding,
+            "^".repeat(underline)
+        );
+
+        output
+    }
+
+    /// Render the diagnostic in one line (e.g. `src/index.html:3:5: error[unclosed-tag]: ...`).
+    pub fn render_short(&self) -> String {
+        match (&self.file, &self.span) {
+            (None, None) => self.to_string(),
+            _ => format!("{}: {}", self.location(), self),
+        }
+    }
+
+    /// Render the diagnostic as a JSON object.
+    pub fn render_json(&self) -> String {
+        serde_json::to_string(self).expect("failed to serialize the diagnostic")
+    }
+
+    /// `file:line:column`, or a part of it
+    fn location(&self) -> String {
+        let position = self
+            .span
+            .as_ref()
+            .map(|span| format!("{}:{}", span.line, span.column));
+
+        match (&self.file, position) {
+            (Some(file), Some(position)) => format!("{}:{}", file, position),
+            (Some(file), None) => file.clone(),
+            (None, Some(position)) => position,
+            (None, None) => String::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code::utils::line_map::build_line_map;
+    use rstest::rstest;
+
+    fn create_diagnostic(content: &str, byte_range: Range<usize>) -> Diagnostic {
+        let line_map = build_line_map(content);
+
+        Diagnostic::error("unclosed-tag", "The tag <marker> is not closed")
+            .with_file("src/index.html")
+            .with_span(Span::new(content, &line_map, byte_range))
+    }
+
+    #[test]
+    fn test_span() {
+        let content = "foo\nあ<marker>\n";
+        let line_map = build_line_map(content);
+
+        assert_eq!(
+            Span::new(content, &line_map, 7..15),
+            Span {
+                byte_start: 7,
+                byte_end: 15,
+                line: 2,
+                column: 2,
+                end_line: 2,
+                end_column: 10,
+            }
+        );
+    }
+
+    #[rstest]
+    #[case(
+        "a\nb\n  <!-- <marker> -->\nc",
+        6..23,
+        "error[unclosed-tag]: The tag <marker> is not closed
+ --> src/index.html:3:3
+  |
+3 |   <!-- <marker> -->
+  |   ^^^^^^^^^^^^^^^^^"
+    )]
+    #[case(
+        "\tあ<marker>\r\nfoo",
+        4..12,
+        "error[unclosed-tag]: The tag <marker> is not closed
+ --> src/index.html:1:3
+  |
+1 | \tあ<marker>
+  | \t ^^^^^^^^"
+    )]
+    #[case(
+        "<marker\nfoo>",
+        0..12,
+        "error[unclosed-tag]: The tag <marker> is not closed
+ --> src/index.html:1:1
+  |
+1 | <marker
+  | ^^^^^^^"
+    )]
+    #[case(
+        "foo",
+        3..3,
+        "error[unclosed-tag]: The tag <marker> is not closed
+ --> src/index.html:1:4
+  |
+1 | foo
+  |    ^"
+    )]
+    #[case(
+        &"\n".repeat(9),
+        9..9,
+        "error[unclosed-tag]: The tag <marker> is not closed
+  --> src/index.html:10:1
+   |
+10 |
+   | ^"
+    )]
+    fn test_render(#[case] content: &str, #[case] range: Range<usize>, #[case] expected: &str) {
+        assert_eq!(create_diagnostic(content, range).render(content), expected);
+    }
+
+    #[test]
+    fn test_render_without_span() {
+        let diagnostic = Diagnostic::warning("not-utf8", "Skipped because it is not UTF-8 text")
+            .with_file("a.bin");
+
+        assert_eq!(
+            diagnostic.render(""),
+            "warning[not-utf8]: Skipped because it is not UTF-8 text\n --> a.bin"
+        );
+        assert_eq!(
+            diagnostic.render_short(),
+            "a.bin: warning[not-utf8]: Skipped because it is not UTF-8 text"
+        );
+    }
+
+    #[test]
+    fn test_render_short() {
+        let content = "a\n  <marker>";
+
+        assert_eq!(
+            create_diagnostic(content, 4..12).render_short(),
+            "src/index.html:2:3: error[unclosed-tag]: The tag <marker> is not closed"
+        );
+        assert_eq!(
+            Diagnostic::error("io", "Permission denied").render_short(),
+            "error[io]: Permission denied"
+        );
+    }
+
+    #[test]
+    fn test_render_json() {
+        let content = "a\n  <marker>";
+
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(
+                &create_diagnostic(content, 4..12).render_json()
+            )
+            .unwrap(),
+            serde_json::json!({
+                "severity": "error",
+                "code": "unclosed-tag",
+                "message": "The tag <marker> is not closed",
+                "file": "src/index.html",
+                "span": {
+                    "byte_start": 4,
+                    "byte_end": 12,
+                    "line": 2,
+                    "column": 3,
+                    "end_line": 2,
+                    "end_column": 11
+                }
+            })
+        );
+    }
+}
@@ -1,4 +1,8 @@
 use crate::tokenizer;
+use std::borrow::Cow;
+use std::iter::Peekable;
+use std::str::CharIndices;
+use thiserror::Error;
 
 #[derive(Debug, PartialEq)]
 pub struct Element<'a> {
@@ -9,126 +13,234 @@ pub struct Element<'a> {
 #[derive(Debug, PartialEq)]
 pub struct Attribute<'a> {
     pub name: &'a str,
-    pub value: Option<&'a str>,
+    /// The value of the attribute. Escaped characters (e.g. `\"`) are unescaped.
+    pub value: Option<Cow<'a, str>>,
 }
 
-pub fn parse<'a>(token: &'a tokenizer::Token) -> Option<Element<'a>> {
-    #[derive(PartialEq)]
-    enum State {
-        NameBegin,
-        Name(usize),
-        NameEnd,
-        ValueBegin,
-        ValueWithNoQuote,
-        ValueWithDoubleQuote(usize),
-        ValueWithSingleQuote(usize),
-        ParseError,
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum ParseErrorKind {
+    #[error("The token is not an element")]
+    NotElement,
+    #[error("The tag name is missing")]
+    MissingName,
+    #[error("Unexpected character '{0}'")]
+    UnexpectedChar(char),
+    #[error("The value of the attribute \"{0}\" is missing")]
+    MissingValue(String),
+    #[error("The quote {0} is not closed")]
+    UnterminatedQuote(char),
+}
+
+#[derive(Error, Debug, PartialEq, Clone)]
+#[error("{kind}")]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    /// The byte position of the error in the source
+    pub byte_pos: usize,
+}
+
+/// Parses the tag name and the attributes of an element token.
+///
+/// ```text
+///  /* < time-limited to="2024-01-01" tz=Asia/Tokyo
+///   *   note="say \"hello\"" > */
+///       ^-----------^  ^----------^  ^---------^
+///       name           attributes (the value can be unquoted)
+///   ^ The prefix of comment continuation lines is skipped.
+/// ```
+///
+/// Spaces, tabs and line breaks separate the attributes.
+/// In a quoted value, `\"`, `\'` and `\\` are unescaped.
+pub fn parse<'a>(token: &'a tokenizer::Token) -> Result<Element<'a>, ParseError> {
+    let tokenizer::TokenKind::Element(element) = &token.kind else {
+        return Err(ParseError {
+            kind: ParseErrorKind::NotElement,
+            byte_pos: token.byte_start,
+        });
+    };
+
+    let trimmed = token.value.trim_start_matches(element.delimiter_start);
+    let target = if element.delimiter_end == tokenizer::END_OF_LINE {
+        trim_end_of_line(trimmed, element.delimiter_start)
+    } else {
+        trimmed.trim_end_matches(element.delimiter_end)
+    };
+
+    let mut scanner = Scanner {
+        target,
+        chars: target.char_indices().peekable(),
+        byte_offset: token.byte_start + token.value.len() - trimmed.len(),
+    };
+
+    scanner.skip_blanks();
+    let name = scanner.name()?;
+    if name.is_empty() {
+        return Err(scanner.error(ParseErrorKind::MissingName));
     }
 
-    match &token.kind {
-        tokenizer::TokenKind::Element(element) => {
-            let (pairs, last_state) = {
-                let target = token
-                    .value
-                    .trim_start_matches(element.delimiter_start)
-                    .trim_end_matches(element.delimiter_end);
-
-                let (mut pairs, last_state) = target.char_indices().fold(
-                    (vec![], State::NameBegin),
-                    |(mut pairs, mut state), (pos, current_char)| {
-                        match state {
-                            State::NameBegin => match current_char {
-                                ' ' => {}
-                                '=' => state = State::ParseError,
-                                '"' => state = State::ParseError,
-                                '\'' => state = State::ParseError,
-                                _ => {
-                                    state = State::Name(pos);
-                                }
-                            },
-                            State::Name(start) => match current_char {
-                                ' ' | '\n' => {
-                                    pairs.push((&target[start..pos], None));
-                                    state = State::NameEnd;
-                                }
-                                '=' => {
-                                    pairs.push((&target[start..pos], None));
-                                    state = State::ValueBegin
-                                }
-                                _ => {}
-                            },
-                            State::NameEnd => match current_char {
-                                ' ' => {}
-                                '=' => state = State::ValueBegin,
-                                _ => {
-                                    state = State::Name(pos);
-                                }
-                            },
-                            State::ValueBegin => match current_char {
-                                ' ' => {}
-                                '"' => {
-                                    state = State::ValueWithDoubleQuote(pos + 1);
-                                }
-                                '\'' => {
-                                    state = State::ValueWithSingleQuote(pos + 1);
-                                }
-                                _ => state = State::ValueWithNoQuote,
-                            },
-                            State::ValueWithDoubleQuote(start) => {
-                                if current_char == '"' {
-                                    pairs.last_mut().unwrap().1 = Some(&target[start..pos]);
-                                    state = State::NameBegin
-                                }
-                            }
-                            State::ValueWithSingleQuote(start) => {
-                                if current_char == '\'' {
-                                    pairs.last_mut().unwrap().1 = Some(&target[start..pos]);
-                                    state = State::NameBegin
-                                }
-                            }
-                            State::ValueWithNoQuote => {
-                                if current_char == ' ' {
-                                    state = State::NameBegin
-                                }
-                            }
-                            State::ParseError => {}
-                        }
-
-                        (pairs, state)
-                    },
-                );
-
-                if let State::Name(start) = last_state {
-                    pairs.push((&target[start..], None));
-                }
+    let mut attrs = vec![];
+    loop {
+        scanner.skip_blanks();
+        if scanner.chars.peek().is_none() {
+            break;
+        }
 
-                (pairs, last_state)
-            };
+        let name = scanner.name()?;
+        scanner.skip_blanks();
+        let value = if scanner.next_if_eq('=') {
+            scanner.skip_blanks();
+            Some(scanner.value(name)?)
+        } else {
+            None
+        };
 
-            if last_state == State::ParseError {
-                return None;
+        attrs.push(Attribute { name, value });
+    }
+
+    Ok(Element { name, attrs })
+}
+
+struct Scanner<'a> {
+    target: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    /// The byte position of the target in the source
+    byte_offset: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn pos(&mut self) -> usize {
+        self.chars
+            .peek()
+            .map(|(pos, _)| *pos)
+            .unwrap_or(self.target.len())
+    }
+
+    fn error(&mut self, kind: ParseErrorKind) -> ParseError {
+        ParseError {
+            kind,
+            byte_pos: self.byte_offset + self.pos(),
+        }
+    }
+
+    fn next_if_eq(&mut self, c: char) -> bool {
+        self.chars.next_if(|(_, current)| *current == c).is_some()
+    }
+
+    /// Skips blanks and the `*` prefix of comment continuation lines.
+    fn skip_blanks(&mut self) {
+        let mut is_line_head = false;
+
+        while let Some((_, c)) = self.chars.peek() {
+            match c {
+                ' ' | '\t' | '\r' => {}
+                '\n' => is_line_head = true,
+                '*' if is_line_head => is_line_head = false,
+                _ => break,
+            }
+            self.chars.next();
+        }
+    }
+
+    fn name(&mut self) -> Result<&'a str, ParseError> {
+        let start = self.pos();
+        if let Some((_, c @ ('=' | '"' | '\''))) = self.chars.peek() {
+            let c = *c;
+            return Err(self.error(ParseErrorKind::UnexpectedChar(c)));
+        }
+
+        while self
+            .chars
+            .next_if(|(_, c)| !is_blank(*c) && *c != '=')
+            .is_some()
+        {}
+
+        Ok(&self.target[start..self.pos()])
+    }
+
+    fn value(&mut self, name: &str) -> Result<Cow<'a, str>, ParseError> {
+        match self.chars.peek() {
+            None => Err(self.error(ParseErrorKind::MissingValue(name.to_string()))),
+            Some((_, quote @ ('"' | '\''))) => {
+                let quote = *quote;
+                let quote_pos = self.byte_offset + self.pos();
+                self.chars.next();
+                self.quoted_value(quote).ok_or(ParseError {
+                    kind: ParseErrorKind::UnterminatedQuote(quote),
+                    byte_pos: quote_pos,
+                })
             }
+            Some(_) => {
+                let start = self.pos();
+                while self.chars.next_if(|(_, c)| !is_blank(*c)).is_some() {}
+
+                Ok(Cow::Borrowed(&self.target[start..self.pos()]))
+            }
+        }
+    }
 
-            let (name, attrs) = (
-                pairs[0].0,
-                pairs[1..]
-                    .iter()
-                    .map(|(name, value)| Attribute {
-                        name,
-                        value: *value,
-                    })
-                    .collect(),
-            );
-
-            Some(Element { name, attrs })
+    /// Reads the value until the closing quote. Returns None if the quote is not closed.
+    fn quoted_value(&mut self, quote: char) -> Option<Cow<'a, str>> {
+        let start = self.pos();
+        // It is allocated only if the value contains escaped characters.
+        let mut unescaped: Option<String> = None;
+
+        loop {
+            let (pos, c) = self.chars.next()?;
+            match c {
+                c if c == quote => {
+                    return Some(match unescaped {
+                        Some(value) => Cow::Owned(value),
+                        None => Cow::Borrowed(&self.target[start..pos]),
+                    });
+                }
+                '\\' => {
+                    let value =
+                        unescaped.get_or_insert_with(|| self.target[start..pos].to_string());
+                    match self.chars.next_if(|(_, c)| matches!(c, '"' | '\'' | '\\')) {
+                        Some((_, escaped)) => value.push(escaped),
+                        None => value.push('\\'),
+                    }
+                }
+                c => {
+                    if let Some(value) = unescaped.as_mut() {
+                        value.push(c);
+                    }
+                }
+            }
         }
-        _ => None,
     }
 }
 
+fn is_blank(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\r' | '\n')
+}
+
+/// Trims the line break of a tag closed at the end of the line.
+/// The closing bracket paired with the start delimiter (e.g. `>` of `# <time-limited>`) is also trimmed.
+fn trim_end_of_line<'a>(target: &'a str, delimiter_start: &str) -> &'a str {
+    let target = target.trim_end_matches([' ', '\t', '\r', '\n']);
+    let closing_bracket = match delimiter_start.chars().last() {
+        Some('<') => '>',
+        Some('[') => ']',
+        Some('{') => '}',
+        Some('(') => ')',
+        _ => return target,
+    };
+
+    target.strip_suffix(closing_bracket).unwrap_or(target)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rstest::rstest;
+
+    fn attr<'a>(name: &'a str, value: Option<&'a str>) -> Attribute<'a> {
+        Attribute {
+            name,
+            value: value.map(Cow::Borrowed),
+        }
+    }
 
     #[test]
     fn test_parse() {
@@ -146,7 +258,7 @@ mod tests {
 
         assert_eq!(
             parse(&token),
-            Some(Element {
+            Ok(Element {
                 name: "hello-world",
                 attrs: vec![],
             })
@@ -166,17 +278,11 @@ mod tests {
 
         assert_eq!(
             parse(&token),
-            Some(Element {
+            Ok(Element {
                 name: "hello-world",
                 attrs: vec![
-                    Attribute {
-                        name: "from",
-                        value: Some("2022-01-01 00:00:00")
-                    },
-                    Attribute {
-                        name: "to",
-                        value: Some("123")
-                    }
+                    attr("from", Some("2022-01-01 00:00:00")),
+                    attr("to", Some("123"))
                 ],
             })
         );
@@ -184,95 +290,133 @@ mod tests {
         let tokens = tokenizer::tokenize(r#"<foo bar>"#, "<", ">");
         assert_eq!(
             parse(&tokens[0]),
-            Some(Element {
+            Ok(Element {
                 name: "foo",
-                attrs: vec![Attribute {
-                    name: "bar",
-                    value: None
-                }],
+                attrs: vec![attr("bar", None)],
             })
         );
 
         let tokens = tokenizer::tokenize("<foo", "<", ">");
-        assert_eq!(parse(&tokens[0]), None);
+        assert_eq!(
+            parse(&tokens[0]).map_err(|e| e.kind),
+            Err(ParseErrorKind::NotElement)
+        );
 
         let tokens = tokenizer::tokenize("foo", "<", ">");
-        assert_eq!(parse(&tokens[0]), None);
+        assert_eq!(
+            parse(&tokens[0]).map_err(|e| e.kind),
+            Err(ParseErrorKind::NotElement)
+        );
 
         let tokens = tokenizer::tokenize("<foo=><bar<><bar>", "<", ">");
         assert_eq!(
             parse(&tokens[0]),
-            Some(Element {
-                name: "foo",
-                attrs: vec![]
+            Err(ParseError {
+                kind: ParseErrorKind::UnexpectedChar('='),
+                byte_pos: 4
             })
         );
         assert_eq!(
             parse(&tokens[1]),
-            Some(Element {
+            Ok(Element {
                 name: "bar<",
                 attrs: vec![]
             })
         );
         assert_eq!(
             parse(&tokens[2]),
-            Some(Element {
+            Ok(Element {
                 name: "bar",
                 attrs: vec![]
             })
         );
 
-        let tokens = tokenizer::tokenize("<foo bar='baz>", "<", ">");
+        let tokens = tokenizer::tokenize("<foo bar=123 baz='456'>", "<", ">");
         assert_eq!(
             parse(&tokens[0]),
-            Some(Element {
+            Ok(Element {
                 name: "foo",
-                attrs: vec![Attribute {
-                    name: "bar",
-                    value: None
-                }],
+                attrs: vec![attr("bar", Some("123")), attr("baz", Some("456"))],
             })
         );
 
-        let tokens = tokenizer::tokenize("<foo bar=123>", "<", ">");
+        let tokens = tokenizer::tokenize("# <foo bar='baz'>\r\n", "# <", tokenizer::END_OF_LINE);
         assert_eq!(
             parse(&tokens[0]),
-            Some(Element {
+            Ok(Element {
                 name: "foo",
-                attrs: vec![Attribute {
-                    name: "bar",
-                    value: None
-                }],
+                attrs: vec![attr("bar", Some("baz"))],
             })
         );
 
-        let tokens = tokenizer::tokenize("<foo bar=123 baz='456'>", "<", ">");
+        let tokens = tokenizer::tokenize("-- foo bar \n", "-- ", tokenizer::END_OF_LINE);
         assert_eq!(
             parse(&tokens[0]),
-            Some(Element {
+            Ok(Element {
                 name: "foo",
-                attrs: vec![
-                    Attribute {
-                        name: "bar",
-                        value: None
-                    },
-                    Attribute {
-                        name: "baz",
-                        value: Some("456")
-                    }
-                ],
+                attrs: vec![attr("bar", None)],
             })
         );
+    }
 
-        let tokens = tokenizer::tokenize("<foo bar\n>", "<", ">");
+    #[rstest]
+    #[case("<foo bar\n>", vec![attr("bar", None)])]
+    #[case("<foo bar\r\n>", vec![attr("bar", None)])]
+    #[case("<foo\tbar=1\tbaz>", vec![attr("bar", Some("1")), attr("baz", None)])]
+    #[case("<foo bar = 'a b'>", vec![attr("bar", Some("a b"))])]
+    #[case("<foo to=2024-01-01 tz=Asia/Tokyo>", vec![attr("to", Some("2024-01-01")), attr("tz", Some("Asia/Tokyo"))])]
+    #[case("<foo bar=\"\">", vec![attr("bar", Some(""))])]
+    #[case("<foo bar=\"it's\" baz='say \"hi\"'>", vec![attr("bar", Some("it's")), attr("baz", Some("say \"hi\""))])]
+    #[case(
+        "<foo\n * bar=\"a\"\n *   baz='b'\n >",
+        vec![attr("bar", Some("a")), attr("baz", Some("b"))]
+    )]
+    #[case("<foo\r\n\t* bar>", vec![attr("bar", None)])]
+    #[case("<foo *bar>", vec![attr("*bar", None)])]
+    fn test_parse_attributes(#[case] input: &str, #[case] expected: Vec<Attribute>) {
+        let tokens = tokenizer::tokenize(input, "<", ">");
         assert_eq!(
             parse(&tokens[0]),
-            Some(Element {
+            Ok(Element {
                 name: "foo",
-                attrs: vec![Attribute {
-                    name: "bar",
-                    value: None
-                }],
+                attrs: expected
+            })
+        );
+    }
+
+    #[rstest]
+    #[case(r#"<foo bar="a \"b\" c">"#, r#"a "b" c"#)]
+    #[case(r#"<foo bar='a \'b\' c'>"#, "a 'b' c")]
+    #[case(r#"<foo bar="a\\b">"#, r#"a\b"#)]
+    #[case(r#"<foo bar="a\nb">"#, r#"a\nb"#)]
+    fn test_parse_escaped_value(#[case] input: &str, #[case] expected: &str) {
+        let tokens = tokenizer::tokenize(input, "<", ">");
+        let el = parse(&tokens[0]).unwrap();
+
+        assert_eq!(el.attrs[0].value.as_deref(), Some(expected));
+        assert!(matches!(el.attrs[0].value, Some(Cow::Owned(_))));
+    }
+
+    #[rstest]
+    //      0123456789012
+    #[case("a<foo bar='baz>", ParseErrorKind::UnterminatedQuote('\''), 10)]
+    #[case("a<foo bar=\"b\\\">", ParseErrorKind::UnterminatedQuote('"'), 10)]
+    #[case("a<foo bar=>", ParseErrorKind::MissingValue("bar".to_string()), 10)]
+    #[case("a<foo =bar>", ParseErrorKind::UnexpectedChar('='), 6)]
+    #[case("a<foo 'bar'>", ParseErrorKind::UnexpectedChar('\''), 6)]
+    #[case("a< >", ParseErrorKind::MissingName, 3)]
+    #[case("a<=foo>", ParseErrorKind::UnexpectedChar('='), 2)]
+    fn test_parse_error(
+        #[case] input: &str,
+        #[case] expected_kind: ParseErrorKind,
+        #[case] expected_byte_pos: usize,
+    ) {
+        let tokens = tokenizer::tokenize(input, "<", ">");
+        assert_eq!(
+            parse(&tokens[1]),
+            Err(ParseError {
+                kind: expected_kind,
+                byte_pos: expected_byte_pos
             })
         );
     }
@@ -1,5 +1,8 @@
 pub mod chiritori;
 pub mod code;
+pub mod diagnostic;
 pub mod element_parser;
 pub mod parser;
+pub mod preset;
 pub mod tokenizer;
+pub mod validator;
@@ -41,7 +41,7 @@ fn tree<'a, 'b, 'c, 'd>(
     tokens: &'a Vec<tokenizer::Token<'a, 'b, 'c>>,
     cursor: usize,
     parts: &mut Vec<ContentPart<'a, 'b, 'c, 'd>>,
-    parent_elements: Vec<&element_parser::Element>,
+    parent_elements: Vec<(&element_parser::Element, &tokenizer::TokenKind)>,
 ) -> (
     usize,
     Option<(
@@ -65,18 +65,18 @@ fn tree<'a, 'b, 'c, 'd>(
             tokenizer::TokenKind::Element(_) => element_parser::parse(t).map_or(
                 State::Content(vec![ContentPart::Text(Text { token: t })]),
                 |el| {
+                    // A tag can only be closed with the same delimiters as the start tag.
                     if el.name.starts_with("/") {
                         let pair_name = el.name.trim_start_matches("/");
-                        if parent_elements
-                            .iter()
-                            .any(|parent_el| parent_el.name == pair_name)
-                        {
+                        if parent_elements.iter().any(|(parent_el, parent_kind)| {
+                            parent_el.name == pair_name && *parent_kind == &t.kind
+                        }) {
                             return State::Closed((t, el));
                         }
                     }
 
                     let mut next_parent_elements = parent_elements.clone();
-                    next_parent_elements.push(&el);
+                    next_parent_elements.push((&el, &t.kind));
                     let mut children = vec![];
                     let (new_cursor, end_part) =
                         tree(tokens, cursor, &mut children, next_parent_elements);
@@ -84,7 +84,9 @@ fn tree<'a, 'b, 'c, 'd>(
                     cursor = new_cursor;
 
                     if let Some((end_token, end_el)) = end_part {
-                        if el.name == end_el.name.trim_start_matches("/") {
+                        if el.name == end_el.name.trim_start_matches("/")
+                            && end_token.kind == t.kind
+                        {
                             State::Content(vec![ContentPart::Element(Element {
                                 start_element: el,
                                 start_token: t,
@@ -160,7 +162,7 @@ mod tests {
                         name: "bar",
                         attrs: vec![element_parser::Attribute {
                             name: "baz",
-                            value: Some("13")
+                            value: Some("13".into())
                         }]
                     },
                     start_token: &tokenizer::Token {
@@ -266,7 +268,7 @@ mod tests {
                     name: "a",
                     attrs: vec![element_parser::Attribute {
                         name: "b",
-                        value: Some("c")
+                        value: Some("c".into())
                     }]
                 },
                 start_token: &tokenizer::Token {
@@ -341,7 +343,7 @@ mod tests {
                     name: "a",
                     attrs: vec![element_parser::Attribute {
                         name: "b",
-                        value: Some("c")
+                        value: Some("c".into())
                     }]
                 },
                 start_token: &tokenizer::Token {
@@ -404,4 +406,35 @@ mod tests {
             }),]
         );
     }
+
+    #[test]
+    fn test_parse_with_multiple_delimiters() {
+        // "/* /a */" does not close "a" opened with "<!--" and "-->".
+        let content = "<!-- a -->x/* /a */y<!-- /a --><!-- b -->/* /b */";
+        let tokens = tokenizer::tokenize_with_delimiters(content, &[("<!--", "-->"), ("/*", "*/")]);
+
+        let parts = parse(&tokens);
+        assert_eq!(parts.len(), 3);
+
+        let ContentPart::Element(el) = &parts[0] else {
+            panic!("The first part is not an element");
+        };
+        assert_eq!(el.start_element.name, "a");
+        assert_eq!(el.start_token.value, "<!-- a -->");
+        assert_eq!(el.end_token.value, "<!-- /a -->");
+        assert_eq!(
+            el.children
+                .iter()
+                .map(|c| match c {
+                    ContentPart::Text(t) => t.token.value,
+                    ContentPart::Element(_) => panic!("Unexpected element"),
+                })
+                .collect::<Vec<_>>(),
+            vec!["x", "/* /a */", "y"]
+        );
+
+        // A tag closed with other delimiters is treated as a text.
+        assert!(matches!(&parts[1], ContentPart::Text(t) if t.token.value == "<!-- b -->"));
+        assert!(matches!(&parts[2], ContentPart::Text(t) if t.token.value == "/* /b */"));
+    }
 }
@@ -0,0 +1,216 @@
+use crate::tokenizer::END_OF_LINE;
+
+const HTML_COMMENT: (&str, &str) = ("<!-- <", "> -->");
+const BLOCK_COMMENT: (&str, &str) = ("/* <", "> */");
+const SLASH_LINE_COMMENT: (&str, &str) = ("// <", END_OF_LINE);
+const HASH_LINE_COMMENT: (&str, &str) = ("# <", END_OF_LINE);
+const DASH_LINE_COMMENT: (&str, &str) = ("-- <", END_OF_LINE);
+
+/// Delimiter pairs for a language, chosen by the name or the file extension.
+#[derive(Debug, PartialEq)]
+pub struct LanguagePreset {
+    pub name: &'static str,
+    pub extensions: &'static [&'static str],
+    pub delimiters: &'static [(&'static str, &'static str)],
+}
+
+impl LanguagePreset {
+    pub fn delimiter_pairs(&self) -> Vec<(String, String)> {
+        self.delimiters
+            .iter()
+            .map(|(start, end)| (start.to_string(), end.to_string()))
+            .collect()
+    }
+}
+
+/// Built-in language presets.
+///
+/// ```text
+///  <!-- <tag> -->   HTML_COMMENT
+///  /* <tag> */      BLOCK_COMMENT
+///  // <tag>         SLASH_LINE_COMMENT (closed at the end of the line)
+///  # <tag>          HASH_LINE_COMMENT  (closed at the end of the line)
+///  -- <tag>         DASH_LINE_COMMENT  (closed at the end of the line)
+/// ```
+pub const LANGUAGE_PRESETS: &[LanguagePreset] = &[
+    LanguagePreset {
+        name: "html",
+        extensions: &["html", "htm", "xhtml"],
+        delimiters: &[HTML_COMMENT],
+    },
+    LanguagePreset {
+        name: "xml",
+        extensions: &["xml", "svg", "xsl"],
+        delimiters: &[HTML_COMMENT],
+    },
+    LanguagePreset {
+        name: "markdown",
+        extensions: &["md", "markdown"],
+        delimiters: &[HTML_COMMENT],
+    },
+    LanguagePreset {
+        name: "vue",
+        extensions: &["vue"],
+        delimiters: &[HTML_COMMENT, BLOCK_COMMENT, SLASH_LINE_COMMENT],
+    },
+    LanguagePreset {
+        name: "svelte",
+        extensions: &["svelte"],
+        delimiters: &[HTML_COMMENT, BLOCK_COMMENT, SLASH_LINE_COMMENT],
+    },
+    LanguagePreset {
+        name: "js",
+        extensions: &["js", "mjs", "cjs", "jsx"],
+        delimiters: &[BLOCK_COMMENT, SLASH_LINE_COMMENT],
+    },
+    LanguagePreset {
+        name: "ts",
+        extensions: &["ts", "mts", "cts", "tsx"],
+        delimiters: &[BLOCK_COMMENT, SLASH_LINE_COMMENT],
+    },
+    LanguagePreset {
+        name: "css",
+        extensions: &["css"],
+        delimiters: &[BLOCK_COMMENT],
+    },
+    LanguagePreset {
+        name: "scss",
+        extensions: &["scss", "less"],
+        delimiters: &[BLOCK_COMMENT, SLASH_LINE_COMMENT],
+    },
+    LanguagePreset {
+        name: "c",
+        extensions: &["c", "h"],
+        delimiters: &[BLOCK_COMMENT, SLASH_LINE_COMMENT],
+    },
+    LanguagePreset {
+        name: "cpp",
+        extensions: &["cpp", "cc", "cxx", "hpp", "hh"],
+        delimiters: &[BLOCK_COMMENT, SLASH_LINE_COMMENT],
+    },
+    LanguagePreset {
+        name: "csharp",
+        extensions: &["cs"],
+        delimiters: &[BLOCK_COMMENT, SLASH_LINE_COMMENT],
+    },
+    LanguagePreset {
+        name: "java",
+        extensions: &["java"],
+        delimiters: &[BLOCK_COMMENT, SLASH_LINE_COMMENT],
+    },
+    LanguagePreset {
+        name: "kotlin",
+        extensions: &["kt", "kts"],
+        delimiters: &[BLOCK_COMMENT, SLASH_LINE_COMMENT],
+    },
+    LanguagePreset {
+        name: "swift",
+        extensions: &["swift"],
+        delimiters: &[BLOCK_COMMENT, SLASH_LINE_COMMENT],
+    },
+    LanguagePreset {
+        name: "go",
+        extensions: &["go"],
+        delimiters: &[BLOCK_COMMENT, SLASH_LINE_COMMENT],
+    },
+    LanguagePreset {
+        name: "rust",
+        extensions: &["rs"],
+        delimiters: &[BLOCK_COMMENT, SLASH_LINE_COMMENT],
+    },
+    LanguagePreset {
+        name: "php",
+        extensions: &["php"],
+        delimiters: &[
+            HTML_COMMENT,
+            BLOCK_COMMENT,
+            SLASH_LINE_COMMENT,
+            HASH_LINE_COMMENT,
+        ],
+    },
+    LanguagePreset {
+        name: "python",
+        extensions: &["py", "pyi"],
+        delimiters: &[HASH_LINE_COMMENT],
+    },
+    LanguagePreset {
+        name: "ruby",
+        extensions: &["rb"],
+        delimiters: &[HASH_LINE_COMMENT],
+    },
+    LanguagePreset {
+        name: "shell",
+        extensions: &["sh", "bash", "zsh"],
+        delimiters: &[HASH_LINE_COMMENT],
+    },
+    LanguagePreset {
+        name: "yaml",
+        extensions: &["yml", "yaml"],
+        delimiters: &[HASH_LINE_COMMENT],
+    },
+    LanguagePreset {
+        name: "toml",
+        extensions: &["toml"],
+        delimiters: &[HASH_LINE_COMMENT],
+    },
+    LanguagePreset {
+        name: "sql",
+        extensions: &["sql"],
+        delimiters: &[DASH_LINE_COMMENT, BLOCK_COMMENT],
+    },
+    LanguagePreset {
+        name: "lua",
+        extensions: &["lua"],
+        delimiters: &[DASH_LINE_COMMENT],
+    },
+];
+
+/// Find the built-in preset by the language name.
+pub fn find(name: &str) -> Option<&'static LanguagePreset> {
+    LANGUAGE_PRESETS.iter().find(|preset| preset.name == name)
+}
+
+/// Find the built-in preset by the file extension (without the leading dot).
+pub fn find_by_extension(extension: &str) -> Option<&'static LanguagePreset> {
+    LANGUAGE_PRESETS
+        .iter()
+        .find(|preset| preset.extensions.contains(&extension))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::collections::HashSet;
+
+    #[rstest]
+    #[case("py", Some("python"))]
+    #[case("tsx", Some("ts"))]
+    #[case("vue", Some("vue"))]
+    #[case("unknown", None)]
+    fn test_find_by_extension(#[case] extension: &str, #[case] expected: Option<&str>) {
+        assert_eq!(find_by_extension(extension).map(|p| p.name), expected);
+    }
+
+    #[test]
+    fn test_find() {
+        assert_eq!(
+            find("python").unwrap().delimiter_pairs(),
+            vec![("# <".to_string(), "\n".to_string())]
+        );
+        assert_eq!(find("unknown"), None);
+    }
+
+    #[test]
+    fn test_presets_are_unique() {
+        let mut names = HashSet::new();
+        let mut extensions = HashSet::new();
+
+        for preset in LANGUAGE_PRESETS {
+            assert!(names.insert(preset.name), "{}", preset.name);
+            for extension in preset.extensions {
+                assert!(extensions.insert(extension), "{}", extension);
+            }
+        }
+    }
+}
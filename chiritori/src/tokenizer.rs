@@ -1,4 +1,16 @@
-use std::str::Chars;
+use aho_corasick::{AhoCorasick, Input, MatchKind};
+use std::ops::Range;
+
+/// The end delimiter that closes a tag at the end of the line (or the end of the source).
+/// It is used for languages that have only line comments.
+///
+/// ```text
+///  delimiters: ("# <", END_OF_LINE)
+///  source:     a\n# <tag>\nb
+///                ^-------^
+///                Element (including the line break)
+/// ```
+pub const END_OF_LINE: &str = "\n";
 
 #[derive(PartialEq, Debug, Clone)]
 pub enum TokenKind<'a, 'b> {
@@ -22,178 +34,197 @@ pub struct Token<'a, 'b, 'c> {
     pub byte_end: usize,
 }
 
-enum State<'a, 'b> {
-    Text,
-    DelimiterStart(Chars<'a>),
-    InDelimiter,
-    DelimiterEnd(Chars<'b>),
-}
-
+/// Splits the source into text tokens and element tokens.
+///
+/// An element token starts at the leftmost occurrence of `delimiter_start` and ends at
+/// the first occurrence of `delimiter_end` after it.
+/// The delimiters are searched with the Aho-Corasick automaton, so overlapping prefixes
+/// (e.g. `<!<!--`) and repeated prefixes of the end delimiter (e.g. `--->`) are matched as well.
+///
+/// ```text
+///  source:  foo<!<!--r--->bar
+///           ^--^^-------^^-^
+///           Text Element  Text
+/// ```
 pub fn tokenize<'a, 'b, 'c>(
     source: &'a str,
     delimiter_start: &'b str,
     delimiter_end: &'c str,
 ) -> Vec<Token<'a, 'b, 'c>> {
-    let (mut tokens, state, byte_start_pos, start_pos, current) = source.char_indices().fold(
-        (vec![], State::Text, 0, 0, 0),
-        |(mut tokens, state, mut byte_start_pos, mut start_pos, current): (
-            Vec<Token<'a, 'b, 'c>>,
-            State,
-            usize,
-            usize,
-            usize,
-        ),
-         (byte_pos, c)| {
-            let (token_kind, next_state) = get_state(&c, delimiter_start, delimiter_end, state);
-
-            if let Some(token_kind) = token_kind {
-                if (byte_pos - byte_start_pos) > 0 {
-                    tokens.push(Token {
-                        value: &source[byte_start_pos..byte_pos],
-                        kind: token_kind,
-                        start: start_pos,
-                        byte_start: byte_start_pos,
-                        end: current,
-                        byte_end: byte_pos,
-                    });
-                }
+    tokenize_with_delimiters(source, &[(delimiter_start, delimiter_end)])
+}
 
-                start_pos = current;
-                byte_start_pos = byte_pos;
-            };
+/// Splits the source into text tokens and element tokens with multiple delimiter pairs in one pass.
+///
+/// All start delimiters are searched at once and the leftmost (and longest) one is taken.
+/// The element token ends at the first occurrence of the end delimiter of the same pair.
+/// A pair whose end delimiter no longer appears is excluded from the search,
+/// except for `END_OF_LINE` which is also closed at the end of the source.
+///
+/// ```text
+///  delimiters: ("<!--", "-->"), ("/*", "*/")
+///  source:     a<!--r-->b/*r*/c
+///               ^------^ ^---^
+///               Element  Element
+/// ```
+pub fn tokenize_with_delimiters<'a, 'b, 'c>(
+    source: &'a str,
+    delimiters: &[(&'b str, &'c str)],
+) -> Vec<Token<'a, 'b, 'c>> {
+    let mut tokens = vec![];
+    let mut cursor = Cursor::default();
 
-            (tokens, next_state, byte_start_pos, start_pos, current + 1)
-        },
-    );
+    let mut active_pairs: Vec<(&'b str, &'c str, AhoCorasick)> = delimiters
+        .iter()
+        .filter(|(start, end)| !start.is_empty() && !end.is_empty())
+        .map(|&(start, end)| (start, end, AhoCorasick::new([end]).unwrap()))
+        .collect();
 
-    let (token_kind, _) = get_state(&' ', delimiter_start, delimiter_end, state);
+    while cursor.byte_pos < source.len() && !active_pairs.is_empty() {
+        let start_matcher = AhoCorasick::builder()
+            .match_kind(MatchKind::LeftmostLongest)
+            .build(active_pairs.iter().map(|(start, _, _)| start))
+            .unwrap();
 
-    let last_byte_pos = source.char_indices().last();
-    let additional_token = match last_byte_pos {
-        Some((byte_pos, _)) => match token_kind {
-            None => Some(Token {
-                value: &source[byte_start_pos..],
-                kind: TokenKind::Text,
-                start: start_pos,
-                byte_start: byte_start_pos,
-                end: current,
-                byte_end: byte_pos + 1,
-            }),
-            _ => Some(Token {
-                value: &source[byte_start_pos..byte_pos + 1],
-                kind: token_kind.unwrap(),
-                start: start_pos,
-                byte_start: byte_start_pos,
-                end: current,
-                byte_end: byte_pos + 1,
-            }),
-        },
-        None => None,
-    };
-
-    if let Some(token) = additional_token {
-        tokens.push(token);
-    }
+        loop {
+            let Some(start) =
+                start_matcher.find(Input::new(source).span(cursor.byte_pos..source.len()))
+            else {
+                // No more elements.
+                active_pairs.clear();
+                break;
+            };
 
-    tokens.into_iter().fold(vec![], |mut acc, cur| {
-        let merged = {
-            let last_token = acc.last_mut();
-            match last_token {
-                Some(last_token) => {
-                    if last_token.kind == TokenKind::Text && cur.kind == TokenKind::Text {
-                        last_token.value = &source[last_token.byte_start..cur.byte_end];
-                        last_token.end = cur.end;
-                        last_token.byte_end = cur.byte_end;
-
-                        true
-                    } else {
-                        false
-                    }
+            let (delimiter_start, delimiter_end, end_matcher) =
+                &active_pairs[start.pattern().as_usize()];
+            let end = match end_matcher.find(Input::new(source).span(start.end()..source.len())) {
+                Some(end) => end.end(),
+                None if *delimiter_end == END_OF_LINE => source.len(),
+                None => {
+                    // The end delimiter of the pair does not appear after here.
+                    active_pairs.remove(start.pattern().as_usize());
+                    break;
                 }
-                None => false,
-            }
-        };
+            };
 
-        if !merged {
-            acc.push(cur);
+            let kind = TokenKind::Element(ElementToken {
+                delimiter_start,
+                delimiter_end,
+            });
+            cursor.push(&mut tokens, source, start.start(), TokenKind::Text);
+            cursor.push(&mut tokens, source, end, kind);
         }
+    }
 
-        acc
-    })
-}
-
-fn check_delimiter_start<'a, 'b>(c: &char, delimiter_start: &'a str) -> State<'a, 'b> {
-    let mut delimiter_start_chars = delimiter_start.chars();
+    cursor.push(&mut tokens, source, source.len(), TokenKind::Text);
 
-    if *c == delimiter_start_chars.next().unwrap() {
-        State::DelimiterStart(delimiter_start_chars)
-    } else {
-        State::Text
-    }
+    tokens
 }
 
-fn get_state<'a, 'b>(
-    c: &char,
-    delimiter_start: &'a str,
-    delimiter_end: &'b str,
-    state: State<'a, 'b>,
-) -> (Option<TokenKind<'a, 'b>>, State<'a, 'b>) {
-    match state {
-        State::Text => match check_delimiter_start(c, delimiter_start) {
-            State::DelimiterStart(delimiter_start_chars) => (
-                Some(TokenKind::Text),
-                State::DelimiterStart(delimiter_start_chars),
-            ),
-            _ => (None, State::Text),
-        },
-        State::DelimiterStart(mut current_chars) => {
-            let current_char = current_chars.next();
-
-            match current_char {
-                Some(current_char) => {
-                    if *c == current_char {
-                        (None, State::DelimiterStart(current_chars))
-                    } else {
-                        (None, State::Text)
-                    }
+/// Finds start delimiters that are not closed with the end delimiter of the pair.
+/// They are left in text tokens because `tokenize_with_delimiters` does not make elements of them.
+/// The byte ranges of the start delimiters are returned with the pair in order of appearance.
+///
+/// ```text
+///  delimiters: ("<!--", "-->")
+///  source:     a<!--r-->b<!--c
+///                        ^--^
+///                        Unterminated
+/// ```
+pub fn find_unterminated_delimiters<'b, 'c>(
+    tokens: &[Token<'_, 'b, 'c>],
+    delimiters: &[(&'b str, &'c str)],
+) -> Vec<(Range<usize>, &'b str, &'c str)> {
+    let mut found: Vec<(Range<usize>, &'b str, &'c str)> = vec![];
+
+    for token in tokens.iter().filter(|t| t.kind == TokenKind::Text) {
+        for &(start, end) in delimiters
+            .iter()
+            .filter(|(start, end)| !start.is_empty() && !end.is_empty() && *end != END_OF_LINE)
+        {
+            for (pos, _) in token.value.match_indices(start) {
+                let byte_start = token.byte_start + pos;
+                // A shorter start delimiter may be a part of a longer one found before.
+                if !found
+                    .iter()
+                    .any(|(range, _, _)| range.contains(&byte_start))
+                {
+                    found.push((byte_start..byte_start + start.len(), start, end));
                 }
-                None => (None, State::InDelimiter),
             }
         }
-        State::InDelimiter => {
-            let mut delimiter_end_chars = delimiter_end.chars();
-            if *c == delimiter_end_chars.next().unwrap() {
-                (None, State::DelimiterEnd(delimiter_end_chars))
-            } else {
-                (None, state)
-            }
-        }
-        State::DelimiterEnd(mut current_chars) => {
-            let current_char = current_chars.next();
-
-            match current_char {
-                Some(current_char) => {
-                    if *c == current_char {
-                        (None, State::DelimiterEnd(current_chars))
-                    } else {
-                        (None, State::InDelimiter)
-                    }
-                }
-                None => (
-                    Some(TokenKind::Element(ElementToken {
-                        delimiter_start,
-                        delimiter_end,
-                    })),
-                    check_delimiter_start(c, delimiter_start),
-                ),
-            }
+    }
+
+    found.sort_by_key(|(range, _, _)| range.start);
+
+    found
+}
+
+#[derive(Default)]
+struct Cursor {
+    byte_pos: usize,
+    char_pos: usize,
+}
+
+impl Cursor {
+    /// Pushes a token from the current position to `byte_end` and moves the cursor to `byte_end`.
+    /// Empty tokens are not pushed.
+    fn push<'a, 'b, 'c>(
+        &mut self,
+        tokens: &mut Vec<Token<'a, 'b, 'c>>,
+        source: &'a str,
+        byte_end: usize,
+        kind: TokenKind<'b, 'c>,
+    ) {
+        if byte_end <= self.byte_pos {
+            return;
         }
+
+        let value = &source[self.byte_pos..byte_end];
+        let char_end = self.char_pos + value.chars().count();
+
+        tokens.push(Token {
+            kind,
+            value,
+            start: self.char_pos,
+            byte_start: self.byte_pos,
+            end: char_end,
+            byte_end,
+        });
+
+        self.byte_pos = byte_end;
+        self.char_pos = char_end;
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("a<!--r-->b", &[("<!--", "-->")], vec![])]
+    #[case("a<!--r-->b<!--c", &[("<!--", "-->")], vec![(10..14, "<!--")])]
+    #[case("<!--a<!--b", &[("<!--", "-->")], vec![(0..4, "<!--"), (5..9, "<!--")])]
+    #[case("a<!--r", &[("<!--", "-->"), ("<!", ">")], vec![(1..5, "<!--")])]
+    #[case("a/*r*/<!--b", &[("<!--", "-->"), ("/*", "*/")], vec![(6..10, "<!--")])]
+    #[case("a# <r>", &[("# <", END_OF_LINE)], vec![])]
+    fn test_find_unterminated_delimiters(
+        #[case] source: &str,
+        #[case] delimiters: &[(&str, &str)],
+        #[case] expected: Vec<(Range<usize>, &str)>,
+    ) {
+        let tokens = tokenize_with_delimiters(source, delimiters);
+
+        assert_eq!(
+            find_unterminated_delimiters(&tokens, delimiters)
+                .into_iter()
+                .map(|(range, start, _)| (range, start))
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
 
     #[test]
     fn test_tokenize() {
@@ -439,4 +470,157 @@ mod tests {
             ]
         );
     }
+
+    #[rstest]
+    #[case("<!<!--r-->g", "<!--", "-->", vec![("<!", false), ("<!--r-->", true), ("g", false)])]
+    #[case("aa<r>", "a<", ">", vec![("a", false), ("a<r>", true)])]
+    #[case("<!--r--->g", "<!--", "-->", vec![("<!--r--->", true), ("g", false)])]
+    #[case("<!--r- -- ->-->", "<!--", "-->", vec![("<!--r- -- ->-->", true)])]
+    #[case("[[r]]]", "[[", "]]", vec![("[[r]]", true), ("]", false)])]
+    #[case("<!--<!--r-->-->", "<!--", "-->", vec![("<!--<!--r-->", true), ("-->", false)])]
+    #[case("<!--r", "<!--", "-->", vec![("<!--r", false)])]
+    #[case("r", "", "", vec![("r", false)])]
+    #[case("", "[", "]", vec![])]
+    #[case("a\n# <r>\nb", "# <", END_OF_LINE, vec![("a\n", false), ("# <r>\n", true), ("b", false)])]
+    #[case("a\n# <r>\r\nb", "# <", END_OF_LINE, vec![("a\n", false), ("# <r>\r\n", true), ("b", false)])]
+    #[case("a\n# <r>", "# <", END_OF_LINE, vec![("a\n", false), ("# <r>", true)])]
+    fn test_tokenize_overlapped_delimiters(
+        #[case] source: &str,
+        #[case] delimiter_start: &str,
+        #[case] delimiter_end: &str,
+        #[case] expected: Vec<(&str, bool)>,
+    ) {
+        let tokens = tokenize(source, delimiter_start, delimiter_end);
+        assert_eq!(
+            tokens
+                .iter()
+                .map(|t| (t.value, matches!(t.kind, TokenKind::Element(_))))
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    /// Naive implementation used as a reference of the tokenizer.
+    fn tokenize_naive<'a>(
+        source: &'a str,
+        delimiter_start: &str,
+        delimiter_end: &str,
+    ) -> Vec<(&'a str, bool)> {
+        let mut result = vec![];
+        let mut pos = 0;
+        while let Some(start) = source[pos..].find(delimiter_start).map(|v| v + pos) {
+            let after_start = start + delimiter_start.len();
+            let Some(end) = source[after_start..]
+                .find(delimiter_end)
+                .map(|v| v + after_start + delimiter_end.len())
+            else {
+                break;
+            };
+            if start > pos {
+                result.push((&source[pos..start], false));
+            }
+            result.push((&source[start..end], true));
+            pos = end;
+        }
+        if pos < source.len() {
+            result.push((&source[pos..], false));
+        }
+        result
+    }
+
+    proptest! {
+        #[test]
+        fn test_tokenize_matches_naive_implementation(
+            source in "[<!\\->r あ]{0,64}",
+            delimiter_start in "[<!-]{1,4}",
+            delimiter_end in "[-!>]{1,3}",
+        ) {
+            let tokens = tokenize(&source, &delimiter_start, &delimiter_end);
+
+            prop_assert_eq!(
+                tokens
+                    .iter()
+                    .map(|t| (t.value, matches!(t.kind, TokenKind::Element(_))))
+                    .collect::<Vec<_>>(),
+                tokenize_naive(&source, &delimiter_start, &delimiter_end)
+            );
+        }
+
+        #[test]
+        fn test_tokenize_covers_whole_source(
+            source in "[<!\\->r あ\n]{0,64}",
+        ) {
+            let tokens = tokenize(&source, "<!--", "-->");
+
+            let mut byte_pos = 0;
+            let mut char_pos = 0;
+            for token in tokens.iter() {
+                prop_assert!(!token.value.is_empty());
+                prop_assert_eq!(token.byte_start, byte_pos);
+                prop_assert_eq!(token.start, char_pos);
+                prop_assert_eq!(token.value, &source[token.byte_start..token.byte_end]);
+                prop_assert_eq!(token.end - token.start, token.value.chars().count());
+                byte_pos = token.byte_end;
+                char_pos = token.end;
+            }
+            prop_assert_eq!(byte_pos, source.len());
+            prop_assert!(tokens
+                .windows(2)
+                .all(|w| w[0].kind != TokenKind::Text || w[1].kind != TokenKind::Text));
+        }
+    }
+
+    #[rstest]
+    #[case("a<!--r-->b/*r*/c", vec![("a", None), ("<!--r-->", Some("<!--")), ("b", None), ("/*r*/", Some("/*")), ("c", None)])]
+    #[case("/*<!--r*/-->", vec![("/*<!--r*/", Some("/*")), ("-->", None)])]
+    #[case("<!--/*r-->*/", vec![("<!--/*r-->", Some("<!--")), ("*/", None)])]
+    #[case("/*r<!--r-->", vec![("/*r", None), ("<!--r-->", Some("<!--"))])]
+    #[case("<!-- /*r*/", vec![("<!-- ", None), ("/*r*/", Some("/*"))])]
+    #[case("<!-- <r> -->", vec![("<!-- <r> -->", Some("<!-- <"))])]
+    #[case("<!-- <r -->", vec![("<!-- <r -->", Some("<!--"))])]
+    fn test_tokenize_with_delimiters(
+        #[case] source: &str,
+        #[case] expected: Vec<(&str, Option<&str>)>,
+    ) {
+        let delimiters = [("<!--", "-->"), ("/*", "*/"), ("<!-- <", "> -->")];
+        let tokens = tokenize_with_delimiters(source, &delimiters);
+        assert_eq!(
+            tokens
+                .iter()
+                .map(|t| (
+                    t.value,
+                    match &t.kind {
+                        TokenKind::Element(el) => Some(el.delimiter_start),
+                        TokenKind::Text => None,
+                    }
+                ))
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn test_tokenize_with_delimiters_covers_whole_source(
+            source in "[<!\\-/*>r あ]{0,64}",
+        ) {
+            let delimiters = [("<!--", "-->"), ("/*", "*/")];
+            let tokens = tokenize_with_delimiters(&source, &delimiters);
+
+            prop_assert_eq!(tokens.iter().map(|t| t.value).collect::<String>(), source.clone());
+            for token in tokens.iter() {
+                if let TokenKind::Element(el) = &token.kind {
+                    // The element is closed by the end delimiter of the same pair.
+                    let pair = delimiters.iter().find(|(start, _)| *start == el.delimiter_start);
+                    prop_assert_eq!(pair.map(|(_, end)| *end), Some(el.delimiter_end));
+                    prop_assert!(token.value.starts_with(el.delimiter_start));
+                    prop_assert!(token.value.ends_with(el.delimiter_end));
+                    prop_assert_eq!(
+                        token.value[el.delimiter_start.len()..].find(el.delimiter_end),
+                        Some(token.value.len() - el.delimiter_start.len() - el.delimiter_end.len())
+                    );
+                }
+            }
+        }
+    }
 }
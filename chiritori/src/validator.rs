@@ -0,0 +1,382 @@
+use crate::code::remover::removal_evaluator::EvaluationError;
+use crate::code::utils::edit_distance::levenshtein;
+use crate::code::utils::line_map::{build_line_map, find_line_column};
+use crate::diagnostic::{Diagnostic, Span};
+use crate::element_parser::{self, ParseErrorKind};
+use crate::tokenizer::{ElementToken, Token, TokenKind};
+use std::ops::Range;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Clone)]
+pub enum ValidationErrorKind {
+    #[error("The tag <{0}> is not closed")]
+    UnclosedTag(String),
+    #[error("The closing tag </{0}> has no opening tag")]
+    OrphanClosingTag(String),
+    #[error(
+        "The closing tag </{name}> crosses the tag <{crossed}>, which is opened inside <{name}>"
+    )]
+    CrossedNesting { name: String, crossed: String },
+    #[error("The closing tag </{name}> is written with \"{closed_with}\", but the tag <{name}> is opened with \"{opened_with}\"")]
+    MismatchedDelimiters {
+        name: String,
+        /// The start delimiter of the start tag
+        opened_with: String,
+        /// The start delimiter of the closing tag
+        closed_with: String,
+    },
+    #[error("Unknown tag name \"{name}\". Did you mean \"{suggestion}\"?")]
+    UnknownTagName { name: String, suggestion: String },
+    #[error("Failed to parse the attributes of the tag: {0}")]
+    InvalidAttribute(ParseErrorKind),
+    #[error("<{tag_name}> {error}")]
+    InvalidElement {
+        tag_name: String,
+        error: EvaluationError,
+    },
+    #[error("The delimiter \"{start}\" is not closed with \"{end}\"")]
+    UnterminatedDelimiter { start: String, end: String },
+    #[error("<{tag_name}> The \"{strategy}\" strategy finds nothing to remove, so the element is left as it is")]
+    IneffectiveStrategy {
+        tag_name: String,
+        strategy: &'static str,
+    },
+}
+
+impl ValidationErrorKind {
+    /// The code used in diagnostics
+    pub fn code(&self) -> &'static str {
+        match self {
+            ValidationErrorKind::UnclosedTag(_) => "unclosed-tag",
+            ValidationErrorKind::OrphanClosingTag(_) => "orphan-closing-tag",
+            ValidationErrorKind::CrossedNesting { .. } => "crossed-nesting",
+            ValidationErrorKind::MismatchedDelimiters { .. } => "mismatched-delimiters",
+            ValidationErrorKind::UnknownTagName { .. } => "unknown-tag-name",
+            ValidationErrorKind::InvalidAttribute(_) => "invalid-attribute",
+            ValidationErrorKind::InvalidElement { error, .. } => error.code(),
+            ValidationErrorKind::UnterminatedDelimiter { .. } => "unterminated-delimiter",
+            ValidationErrorKind::IneffectiveStrategy { .. } => "ineffective-strategy",
+        }
+    }
+}
+
+#[derive(Error, Debug, PartialEq, Clone)]
+#[error("{line}:{column}: {kind}")]
+pub struct ValidationError {
+    pub kind: ValidationErrorKind,
+    /// The byte range of the tag (from the position of the failure for `InvalidAttribute`)
+    pub byte_range: Range<usize>,
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column number (counted in characters)
+    pub column: usize,
+}
+
+impl ValidationError {
+    pub fn new(
+        content: &str,
+        line_map: &[usize],
+        kind: ValidationErrorKind,
+        byte_range: Range<usize>,
+    ) -> Self {
+        let (line, column) = find_line_column(content, line_map, byte_range.start);
+
+        Self {
+            kind,
+            byte_range,
+            line,
+            column,
+        }
+    }
+
+    pub fn to_diagnostic(&self, content: &str, line_map: &[usize]) -> Diagnostic {
+        Diagnostic::error(self.kind.code(), self.kind.to_string()).with_span(Span::new(
+            content,
+            line_map,
+            self.byte_range.clone(),
+        ))
+    }
+}
+
+/// Validate the structure of tags.
+///
+/// The following problems are reported.
+///
+/// - Start tags that are not closed
+/// - Closing tags that have no start tag
+/// - Crossed nesting (e.g. `<a><b></a></b>`)
+/// - Closing tags written with other delimiters than the start tag (e.g. `<!-- <a> -->` and `/* </a> */`)
+/// - Tag names that look like typos of the known tag names
+/// - Tags whose attributes cannot be parsed
+///
+/// Tags of other names (e.g. words of plain comments) are ignored.
+/// The errors are sorted by position.
+pub fn validate(content: &str, tokens: &[Token], known_tag_names: &[&str]) -> Vec<ValidationError> {
+    let line_map = build_line_map(content);
+    let mut errors = vec![];
+    // Opened tags (name, delimiters, byte range)
+    let mut stack: Vec<(&str, &ElementToken, Range<usize>)> = vec![];
+    // Tags that have already been reported as crossed. Their closing tags are not reported again.
+    let mut crossed: Vec<(&str, &ElementToken)> = vec![];
+
+    let mut report = |kind: ValidationErrorKind, range: Range<usize>| {
+        errors.push(ValidationError::new(content, &line_map, kind, range));
+    };
+
+    for (token, delimiters) in tokens.iter().filter_map(|t| match &t.kind {
+        TokenKind::Element(delimiters) => Some((t, delimiters)),
+        TokenKind::Text => None,
+    }) {
+        let range = token.byte_start..token.byte_end;
+        let el = match element_parser::parse(token) {
+            Ok(el) => el,
+            Err(e) => {
+                // Reported at the position where the parse failed.
+                report(
+                    ValidationErrorKind::InvalidAttribute(e.kind),
+                    e.byte_pos..token.byte_end,
+                );
+                continue;
+            }
+        };
+
+        let (is_closing, name) = match el.name.strip_prefix('/') {
+            Some(name) => (true, name),
+            None => (false, el.name),
+        };
+
+        if !known_tag_names.contains(&name) {
+            // Other names (e.g. words of plain comments) are not tags and left alone.
+            if let Some(suggestion) = find_similar_name(name, known_tag_names) {
+                report(
+                    ValidationErrorKind::UnknownTagName {
+                        name: name.to_string(),
+                        suggestion: suggestion.to_string(),
+                    },
+                    range,
+                );
+            }
+            continue;
+        }
+
+        if !is_closing {
+            stack.push((name, delimiters, range));
+            continue;
+        }
+
+        // A tag can only be closed with the same delimiters as the start tag.
+        match stack.iter().rposition(|(opened, opened_delimiters, _)| {
+            *opened == name && *opened_delimiters == delimiters
+        }) {
+            Some(pos) => {
+                for (inner, inner_delimiters, _) in stack.drain(pos + 1..) {
+                    report(
+                        ValidationErrorKind::CrossedNesting {
+                            name: name.to_string(),
+                            crossed: inner.to_string(),
+                        },
+                        range.clone(),
+                    );
+                    crossed.push((inner, inner_delimiters));
+                }
+                stack.pop();
+            }
+            None => match crossed.iter().position(|c| *c == (name, delimiters)) {
+                Some(pos) => {
+                    crossed.remove(pos);
+                }
+                None => match stack.iter().rposition(|(opened, _, _)| *opened == name) {
+                    // Reported only once instead of together with the unclosed start tag
+                    Some(pos) => {
+                        let (_, opened_delimiters, _) = stack.remove(pos);
+                        report(
+                            ValidationErrorKind::MismatchedDelimiters {
+                                name: name.to_string(),
+                                opened_with: opened_delimiters.delimiter_start.to_string(),
+                                closed_with: delimiters.delimiter_start.to_string(),
+                            },
+                            range,
+                        );
+                    }
+                    None => report(
+                        ValidationErrorKind::OrphanClosingTag(name.to_string()),
+                        range,
+                    ),
+                },
+            },
+        }
+    }
+
+    for (name, _, range) in stack {
+        report(ValidationErrorKind::UnclosedTag(name.to_string()), range);
+    }
+
+    errors.sort_by_key(|e| e.byte_range.start);
+
+    errors
+}
+
+/// Find the known tag name similar to the name.
+fn find_similar_name<'a>(name: &str, known_tag_names: &[&'a str]) -> Option<&'a str> {
+    known_tag_names
+        .iter()
+        .map(|known| (known, levenshtein(name, known)))
+        .filter(|(known, distance)| *distance <= (known.chars().count() / 3).clamp(1, 2))
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(known, _)| *known)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenizer::{tokenize, tokenize_with_delimiters};
+    use rstest::rstest;
+
+    fn kinds(content: &str) -> Vec<(ValidationErrorKind, usize, usize)> {
+        let tokens = tokenize(content, "<", ">");
+        validate(content, &tokens, &["time-limited", "marker"])
+            .into_iter()
+            .map(|e| (e.kind, e.line, e.column))
+            .collect()
+    }
+
+    #[rstest]
+    #[case("<time-limited>a</time-limited>", vec![])]
+    #[case("<marker><time-limited>a</time-limited></marker>", vec![])]
+    #[case(
+        "a\n <time-limited>a",
+        vec![(ValidationErrorKind::UnclosedTag("time-limited".to_string()), 2, 2)]
+    )]
+    #[case(
+        "a</marker>",
+        vec![(ValidationErrorKind::OrphanClosingTag("marker".to_string()), 1, 2)]
+    )]
+    #[case(
+        "<marker><time-limited></marker></time-limited>",
+        vec![(
+            ValidationErrorKind::CrossedNesting {
+                name: "marker".to_string(),
+                crossed: "time-limited".to_string()
+            },
+            1,
+            23
+        )]
+    )]
+    #[case(
+        "<time-limted>a</time-limited>",
+        vec![
+            (
+                ValidationErrorKind::UnknownTagName {
+                    name: "time-limted".to_string(),
+                    suggestion: "time-limited".to_string()
+                },
+                1,
+                1
+            ),
+            (ValidationErrorKind::OrphanClosingTag("time-limited".to_string()), 1, 15),
+        ]
+    )]
+    #[case(
+        "<marker>a</makrer>",
+        vec![
+            (ValidationErrorKind::UnclosedTag("marker".to_string()), 1, 1),
+            (
+                ValidationErrorKind::UnknownTagName {
+                    name: "makrer".to_string(),
+                    suggestion: "marker".to_string()
+                },
+                1,
+                10
+            ),
+        ]
+    )]
+    #[case("<div>a</div>", vec![])]
+    // Plain comments
+    #[case("<hello world>", vec![])]
+    #[case("<time-limited><hello world>a</time-limited>", vec![])]
+    #[case(
+        "<marker a=\"b\"=c>a</marker>",
+        vec![
+            (ValidationErrorKind::InvalidAttribute(ParseErrorKind::UnexpectedChar('=')), 1, 14),
+            (ValidationErrorKind::OrphanClosingTag("marker".to_string()), 1, 18),
+        ]
+    )]
+    fn test_validate(
+        #[case] content: &str,
+        #[case] expected: Vec<(ValidationErrorKind, usize, usize)>,
+    ) {
+        assert_eq!(kinds(content), expected);
+    }
+
+    #[rstest]
+    #[case("<!-- <marker> --><!-- </marker> -->", vec![])]
+    #[case("<!-- <marker> -->/* <marker> */a/* </marker> */<!-- </marker> -->", vec![])]
+    #[case(
+        "<!-- <marker> -->a/* </marker> */",
+        vec![(
+            ValidationErrorKind::MismatchedDelimiters {
+                name: "marker".to_string(),
+                opened_with: "<!-- <".to_string(),
+                closed_with: "/* <".to_string(),
+            },
+            1,
+            19
+        )]
+    )]
+    #[case(
+        "<!-- <marker> -->/* <marker> */<!-- </marker> -->/* </marker> */",
+        vec![(
+            ValidationErrorKind::CrossedNesting {
+                name: "marker".to_string(),
+                crossed: "marker".to_string()
+            },
+            1,
+            32
+        )]
+    )]
+    fn test_validate_with_delimiters(
+        #[case] content: &str,
+        #[case] expected: Vec<(ValidationErrorKind, usize, usize)>,
+    ) {
+        let tokens = tokenize_with_delimiters(content, &[("<!-- <", "> -->"), ("/* <", "> */")]);
+
+        assert_eq!(
+            validate(content, &tokens, &["time-limited", "marker"])
+                .into_iter()
+                .map(|e| (e.kind, e.line, e.column))
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_validate_byte_range() {
+        //             0         1
+        //             012345678901234567
+        let content = "あ<marker>";
+        let tokens = tokenize(content, "<", ">");
+
+        assert_eq!(
+            validate(content, &tokens, &["marker"]),
+            vec![ValidationError {
+                kind: ValidationErrorKind::UnclosedTag("marker".to_string()),
+                byte_range: 3..11,
+                line: 1,
+                column: 2,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_to_diagnostic() {
+        let content = "a\n<marker>";
+        let tokens = tokenize(content, "<", ">");
+        let line_map = build_line_map(content);
+        let diagnostic =
+            validate(content, &tokens, &["marker"])[0].to_diagnostic(content, &line_map);
+
+        assert_eq!(diagnostic.code, "unclosed-tag");
+        assert_eq!(diagnostic.message, "The tag <marker> is not closed");
+        assert_eq!(diagnostic.span, Some(Span::new(content, &line_map, 2..10)));
+    }
+}